@@ -0,0 +1,11 @@
+use std::io;
+
+pub mod quest;
+mod rng;
+pub mod sonar;
+pub mod submarine;
+
+/// Error for input that does not follow its expected format.
+pub(crate) fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
@@ -1,20 +1,20 @@
-mod quest;
-mod sonar;
-mod submarine;
-
-use sonar::Sonar;
-use std::io;
-use submarine::{bingo::BingoSystem, diagnostic::DiagnosticModule, Submarine};
-
-use crate::{
+use adventofcode2021::{
     quest::{align_crabs::CrabSwarm, seven_segments::SevenSegments},
+    sonar::Sonar,
     submarine::{
-        computer::packet::Packet, lanternfish::LanternFishSim, navigation::NavigationSystem,
-        octopus::OctopusSim,
+        bingo::BingoSystem, computer::packet::Packet, diagnostic::DiagnosticModule,
+        lanternfish::LanternFishSim, navigation::NavigationSystem, octopus::OctopusSim, Submarine,
     },
 };
+use std::io;
 
 fn main() -> io::Result<()> {
+    if std::env::args().nth(1).as_deref() == Some("bingo") {
+        // Call numbers by hand over the day 4 boards.
+        let bingo_system = BingoSystem::from_file("data/day04.txt")?;
+        return bingo_system.caller().run(io::stdin().lock(), io::stdout());
+    }
+
     let input = "data/day01.txt";
     let reading = Sonar::sweep(input)?;
     println!("Day 1, part 1 => {}", Sonar::measure_width(&reading, 1));
@@ -31,12 +31,21 @@ fn main() -> io::Result<()> {
 
     let input = "data/day03.txt";
     let diagnostic_module = DiagnosticModule::from_file(input)?;
-    println!("Day 3, part 1 => {:?}", diagnostic_module.generate_report());
+    let report = diagnostic_module.generate_report()?;
+    println!("Day 3, part 1 => {}", report.power_consumption);
+    println!("Day 3, part 2 => {}", report.life_support_rating);
+    println!("{}", report);
 
     let input = "data/day04.txt";
     let bingo_system = BingoSystem::from_file(input)?;
-    println!("Day 4, part 1 => {}", bingo_system.bingo_to_win());
-    println!("Day 4, part 2 => {}", bingo_system.bingo_to_lose());
+    match bingo_system.bingo_to_win() {
+        Some(score) => println!("Day 4, part 1 => {}", score),
+        None => println!("Day 4, part 1 => no winner"),
+    }
+    match bingo_system.bingo_to_lose() {
+        Some(score) => println!("Day 4, part 2 => {}", score),
+        None => println!("Day 4, part 2 => no winner"),
+    }
 
     let input = "data/day05.txt";
     let vent_lines = Sonar::scan_hydrothermal_vents_file(input)?;
@@ -51,8 +60,8 @@ fn main() -> io::Result<()> {
 
     let input = "data/day06.txt";
     let sim = LanternFishSim::init_pool_from_file(input)?;
-    println!("Day 6, part 1 => {}", sim.run(80));
-    println!("Day 6, part 1 => {}", sim.run(256));
+    println!("Day 6, part 1 => {}", sim.run(80)?);
+    println!("Day 6, part 1 => {}", sim.run(256)?);
 
     let input = "data/day07.txt";
     let crab_swarm = CrabSwarm::init_from_file(input)?;
@@ -65,7 +74,10 @@ fn main() -> io::Result<()> {
     let input = "data/day08.txt";
     let input = SevenSegments::input_from_file(input)?;
     println!("Day 8, part 1 => {}", SevenSegments::count_1478(&input));
-    println!("Day 8, part 2 => {}", SevenSegments::decode_display(&input));
+    println!(
+        "Day 8, part 2 => {}",
+        SevenSegments::decode_display(&input)?
+    );
 
     let input = "data/day09.txt";
     let height_map = Sonar::heightmap_from_file(input)?;
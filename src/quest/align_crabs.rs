@@ -1,12 +1,15 @@
+pub mod alignment;
+pub mod facility;
+pub mod fuel;
+
 use std::{
-    cmp::Ordering,
-    collections::HashMap,
+    collections::BTreeMap,
     fs::File,
     io::{self, Read},
 };
 
 pub struct CrabSwarm {
-    positions: HashMap<usize, usize>,
+    positions: BTreeMap<usize, usize>,
 }
 
 impl CrabSwarm {
@@ -18,49 +21,47 @@ impl CrabSwarm {
     }
 
     pub fn new(input: &str) -> io::Result<CrabSwarm> {
-        let mut positions = HashMap::new();
+        let mut positions = BTreeMap::new();
         for pos in input.trim().split(',').map(|s| s.parse::<usize>().unwrap()) {
             *positions.entry(pos).or_default() += 1;
         }
         Ok(CrabSwarm { positions })
     }
 
+    /// With linear costs the median is always an optimal target.
     pub fn best_alignment(&self) -> usize {
-        let mut min = usize::MAX;
-        for &pos in self.positions.keys() {
-            let mut cand = 0;
-            for (p, v) in self.positions.iter() {
-                match pos.cmp(p) {
-                    Ordering::Greater => cand += v * (pos - p),
-                    Ordering::Less => cand += v * (p - pos),
-                    Ordering::Equal => (),
-                }
-            }
-            if cand < min {
-                min = cand;
+        let crabs: usize = self.positions.values().sum();
+        let mut seen = 0;
+        for (&pos, &v) in self.positions.iter() {
+            seen += v;
+            if 2 * seen >= crabs {
+                return self.fuel(pos, |changes| changes);
             }
         }
-        min
+        0
     }
 
+    /// With triangular costs the real optimum lies within half a step of
+    /// the mean, so the integer one is at most a step beyond it.
     pub fn best_alignment_for_crab_engine(&self) -> usize {
-        let &left = self.positions.keys().min().unwrap();
-        let &right = self.positions.keys().max().unwrap();
-        let mut min = usize::MAX;
-        for pos in left..=right {
-            let mut cand = 0;
-            for (p, v) in self.positions.iter() {
-                match pos.cmp(p) {
-                    Ordering::Greater => cand += v * crab_engine_fuel_cost(pos - p),
-                    Ordering::Less => cand += v * crab_engine_fuel_cost(p - pos),
-                    Ordering::Equal => (),
-                }
-            }
-            if cand < min {
-                min = cand;
-            }
+        let crabs: usize = self.positions.values().sum();
+        if crabs == 0 {
+            return 0;
         }
-        min
+        let sum: usize = self.positions.iter().map(|(p, v)| p * v).sum();
+        let floor = sum / crabs;
+        let ceil = floor + usize::from(!sum.is_multiple_of(crabs));
+        (floor.saturating_sub(1)..=ceil + 1)
+            .map(|pos| self.fuel(pos, crab_engine_fuel_cost))
+            .min()
+            .unwrap()
+    }
+
+    fn fuel(&self, target: usize, cost: impl Fn(usize) -> usize) -> usize {
+        self.positions
+            .iter()
+            .map(|(&p, &v)| v * cost(p.abs_diff(target)))
+            .sum()
     }
 }
 
@@ -79,12 +80,38 @@ mod tests {
         assert_eq!(168, crab_swarm.best_alignment_for_crab_engine());
     }
 
+    #[test]
+    fn test_closed_form_matches_scan() {
+        let mut seed = 7u64;
+        for _ in 0..50 {
+            let crabs = (0..1 + seed % 20)
+                .map(|_| {
+                    seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                    ((seed >> 33) % 100).to_string()
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            let crab_swarm = CrabSwarm::new(&crabs).expect("parse error");
+            let scan = |cost: fn(usize) -> usize| {
+                (0..100)
+                    .map(|pos| crab_swarm.fuel(pos, cost))
+                    .min()
+                    .unwrap()
+            };
+            assert_eq!(scan(|changes| changes), crab_swarm.best_alignment());
+            assert_eq!(
+                scan(crab_engine_fuel_cost),
+                crab_swarm.best_alignment_for_crab_engine()
+            );
+        }
+    }
+
     #[test]
     fn test_crab_engine_fuel_cost() {
         assert_eq!(66, crab_engine_fuel_cost(16 - 5));
         assert_eq!(10, crab_engine_fuel_cost(5 - 1));
         assert_eq!(6, crab_engine_fuel_cost(5 - 2));
-        assert_eq!(15, crab_engine_fuel_cost(5 - 0));
+        assert_eq!(15, crab_engine_fuel_cost(5));
         assert_eq!(45, crab_engine_fuel_cost(14 - 5));
     }
 }
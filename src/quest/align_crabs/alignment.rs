@@ -0,0 +1,153 @@
+use std::ops::RangeInclusive;
+
+use super::{
+    fuel::{best_target, mixed_fuel, span, FuelCost},
+    CrabSwarm,
+};
+
+/// Where a swarm should meet and what each crab pays to get there.
+#[derive(PartialEq, Debug)]
+pub struct Alignment {
+    pub target: usize,
+    /// Every target with the same least fuel, `target` first among them.
+    pub tied: Tied,
+    pub fuel: usize,
+    pub moves: Vec<CrabMove>,
+}
+
+/// Targets sharing the least fuel.
+#[derive(PartialEq, Debug)]
+pub enum Tied {
+    /// One run of positions, as convex costs always give.
+    Run(RangeInclusive<usize>),
+    /// Positions that may be apart, in ascending order.
+    Positions(Vec<usize>),
+}
+
+/// The crabs starting at one position.
+#[derive(PartialEq, Debug)]
+pub struct CrabMove {
+    pub from: usize,
+    pub crabs: usize,
+    pub fuel: usize,
+}
+
+impl CrabSwarm {
+    pub fn plan_alignment(&self, cost: &dyn FuelCost) -> Option<Alignment> {
+        let groups = [(self, cost)];
+        let target = best_target(&groups)?;
+        let fuel = mixed_fuel(&groups, target);
+        let tied = if cost.is_convex() {
+            // Fuel never drops right of the leftmost optimum, so bisect for
+            // the last target still paying the least.
+            let (mut lo, mut hi) = (target, span(&groups)?.1);
+            while lo < hi {
+                let mid = hi - (hi - lo) / 2;
+                if mixed_fuel(&groups, mid) == fuel {
+                    lo = mid;
+                } else {
+                    hi = mid - 1;
+                }
+            }
+            Tied::Run(target..=lo)
+        } else {
+            Tied::Positions(
+                self.cost_curve(cost)
+                    .into_iter()
+                    .filter(|&(_, f)| f == fuel)
+                    .map(|(pos, _)| pos)
+                    .collect(),
+            )
+        };
+        let moves = self
+            .positions
+            .iter()
+            .map(|(&from, &crabs)| CrabMove {
+                from,
+                crabs,
+                fuel: crabs * cost.cost(from, target),
+            })
+            .collect();
+        Some(Alignment {
+            target,
+            tied,
+            fuel,
+            moves,
+        })
+    }
+
+    /// Total fuel for every target from the leftmost to the rightmost crab.
+    pub fn cost_curve(&self, cost: &dyn FuelCost) -> Vec<(usize, usize)> {
+        match span(&[(self, cost)]) {
+            Some((left, right)) => (left..=right)
+                .map(|pos| (pos, self.fuel_with(pos, cost)))
+                .collect(),
+            None => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CrabMove, Tied};
+    use crate::quest::align_crabs::{
+        fuel::{Capped, CrabEngine, Linear},
+        CrabSwarm,
+    };
+
+    #[test]
+    fn test_plan_alignment() {
+        let crab_swarm = CrabSwarm::new("16,1,2,0,4,2,7,1,2,14").expect("parse error");
+        let alignment = crab_swarm.plan_alignment(&Linear).unwrap();
+        assert_eq!(2, alignment.target);
+        assert_eq!(Tied::Run(2..=2), alignment.tied);
+        assert_eq!(37, alignment.fuel);
+        assert_eq!(
+            CrabMove {
+                from: 1,
+                crabs: 2,
+                fuel: 2
+            },
+            alignment.moves[1]
+        );
+        assert_eq!(37, alignment.moves.iter().map(|m| m.fuel).sum::<usize>());
+
+        let alignment = crab_swarm.plan_alignment(&CrabEngine).unwrap();
+        assert_eq!((5, 168), (alignment.target, alignment.fuel));
+    }
+
+    #[test]
+    fn test_ties_and_curve() {
+        let crab_swarm = CrabSwarm::new("0,4").expect("parse error");
+        let alignment = crab_swarm.plan_alignment(&Linear).unwrap();
+        assert_eq!(Tied::Run(0..=4), alignment.tied);
+        assert_eq!(
+            vec![(0, 4), (1, 4), (2, 4), (3, 4), (4, 4)],
+            crab_swarm.cost_curve(&Linear)
+        );
+
+        // Capped costs can tie on separate positions.
+        let capped = Capped {
+            cost: CrabEngine,
+            cap: 3,
+        };
+        assert_eq!(
+            vec![(0, 3), (1, 4), (2, 6), (3, 4), (4, 3)],
+            crab_swarm.cost_curve(&capped)
+        );
+        assert_eq!(
+            Tied::Positions(vec![0, 4]),
+            crab_swarm.plan_alignment(&capped).unwrap().tied
+        );
+
+        // Wide ties are bounded without visiting every position.
+        let crab_swarm = CrabSwarm::new("0,50000000").expect("parse error");
+        let alignment = crab_swarm.plan_alignment(&Linear).unwrap();
+        assert_eq!(Tied::Run(0..=50_000_000), alignment.tied);
+        let crab_swarm = CrabSwarm::new("0,2,4,6").expect("parse error");
+        assert_eq!(
+            Tied::Run(2..=4),
+            crab_swarm.plan_alignment(&Linear).unwrap().tied
+        );
+    }
+}
@@ -0,0 +1,378 @@
+use std::io;
+
+use super::CrabSwarm;
+use crate::invalid;
+
+/// How far a crab travels between two points.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Metric {
+    Manhattan,
+    Euclidean,
+}
+
+impl Metric {
+    pub fn distance(&self, a: &[f64], b: &[f64]) -> f64 {
+        let deltas = a.iter().zip(b).map(|(x, y)| (x - y).abs());
+        match self {
+            Metric::Manhattan => deltas.sum(),
+            Metric::Euclidean => deltas.map(|d| d * d).sum::<f64>().sqrt(),
+        }
+    }
+
+    /// Point closest in total to `points`: the per-axis median under
+    /// Manhattan, the geometric median (Weiszfeld) under Euclidean.
+    fn center(&self, points: &[&[f64]]) -> Vec<f64> {
+        let dims = points[0].len();
+        match self {
+            Metric::Manhattan => (0..dims)
+                .map(|d| {
+                    let mut axis = points.iter().map(|p| p[d]).collect::<Vec<_>>();
+                    axis.sort_by(f64::total_cmp);
+                    axis[(axis.len() - 1) / 2]
+                })
+                .collect(),
+            Metric::Euclidean => {
+                let n = points.len() as f64;
+                let mut center = (0..dims)
+                    .map(|d| points.iter().map(|p| p[d]).sum::<f64>() / n)
+                    .collect::<Vec<_>>();
+                for _ in 0..1000 {
+                    let mut sum = vec![0.0; dims];
+                    let mut weight = 0.0;
+                    for p in points {
+                        let distance = self.distance(p, &center);
+                        if distance < 1e-12 {
+                            continue;
+                        }
+                        for d in 0..dims {
+                            sum[d] += p[d] / distance;
+                        }
+                        weight += 1.0 / distance;
+                    }
+                    if weight == 0.0 {
+                        break;
+                    }
+                    let next = sum.iter().map(|s| s / weight).collect::<Vec<_>>();
+                    let moved = self.distance(&next, &center);
+                    center = next;
+                    if moved < 1e-12 {
+                        break;
+                    }
+                }
+                center
+            }
+        }
+    }
+}
+
+/// Crabs at points in any number of dimensions, one `x,y[,z...]` per line.
+pub struct CrabCloud {
+    crabs: Vec<Vec<f64>>,
+}
+
+/// One meeting point and the crabs sent to it.
+#[derive(PartialEq, Debug)]
+pub struct Meeting {
+    pub target: Vec<f64>,
+    /// Indices of the crabs, in input order.
+    pub crabs: Vec<usize>,
+    pub fuel: f64,
+}
+
+impl CrabCloud {
+    pub fn new(input: &str) -> io::Result<CrabCloud> {
+        let mut crabs: Vec<Vec<f64>> = vec![];
+        for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let crab = line
+                .split(',')
+                .map(|s| s.trim().parse::<i64>().map(|c| c as f64))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid(format!("bad crab position {:?}", line)))?;
+            if crabs.first().is_some_and(|first| first.len() != crab.len()) {
+                return Err(invalid(format!("crab {:?} has the wrong dimensions", line)));
+            }
+            crabs.push(crab);
+        }
+        Ok(CrabCloud { crabs })
+    }
+
+    pub fn best_alignment(&self, metric: Metric) -> Option<Meeting> {
+        self.best_k_alignment(1, metric).pop()
+    }
+
+    /// Split the crabs between `k` meeting points, alternating between
+    /// sending each crab to its nearest point and re-centering each point
+    /// on its crabs. This settles on a local optimum of the k-median
+    /// problem, seeded with crabs spread as far apart as possible.
+    pub fn best_k_alignment(&self, k: usize, metric: Metric) -> Vec<Meeting> {
+        if k == 0 || self.crabs.is_empty() {
+            return vec![];
+        }
+        let mut targets = vec![self.crabs[0].clone()];
+        while targets.len() < k.min(self.crabs.len()) {
+            let farthest = self
+                .crabs
+                .iter()
+                .max_by(|a, b| {
+                    nearest(&targets, a, metric)
+                        .1
+                        .total_cmp(&nearest(&targets, b, metric).1)
+                })
+                .unwrap();
+            targets.push(farthest.clone());
+        }
+
+        let mut assignment = vec![usize::MAX; self.crabs.len()];
+        for _ in 0..100 {
+            let next = self
+                .crabs
+                .iter()
+                .map(|crab| nearest(&targets, crab, metric).0)
+                .collect::<Vec<_>>();
+            if next == assignment {
+                break;
+            }
+            assignment = next;
+            for (t, target) in targets.iter_mut().enumerate() {
+                let points = self.group(&assignment, t);
+                if !points.is_empty() {
+                    *target = metric.center(&points);
+                }
+            }
+        }
+
+        targets
+            .into_iter()
+            .enumerate()
+            .map(|(t, target)| {
+                let crabs = (0..self.crabs.len())
+                    .filter(|&c| assignment[c] == t)
+                    .collect::<Vec<_>>();
+                let fuel = crabs
+                    .iter()
+                    .map(|&c| metric.distance(&self.crabs[c], &target))
+                    .sum();
+                Meeting {
+                    target,
+                    crabs,
+                    fuel,
+                }
+            })
+            .collect()
+    }
+
+    fn group(&self, assignment: &[usize], target: usize) -> Vec<&[f64]> {
+        self.crabs
+            .iter()
+            .zip(assignment)
+            .filter(|(_, &t)| t == target)
+            .map(|(crab, _)| crab.as_slice())
+            .collect()
+    }
+}
+
+fn nearest(targets: &[Vec<f64>], crab: &[f64], metric: Metric) -> (usize, f64) {
+    targets
+        .iter()
+        .map(|target| metric.distance(target, crab))
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+}
+
+impl CrabSwarm {
+    /// Exact least linear fuel to gather the swarm at `k` positions, with
+    /// the positions chosen. Each group is a contiguous run of crabs meeting
+    /// at its median, found by dynamic programming over the sorted positions.
+    /// The best split point only moves right as the run grows, so each row
+    /// is filled by divide and conquer in `O(n log n)` segment costs.
+    pub fn best_k_alignment(&self, k: usize) -> Option<(Vec<usize>, usize)> {
+        let runs = Runs::new(self);
+        let n = runs.positions.len();
+        if n == 0 {
+            return Some((vec![], 0));
+        }
+        if k == 0 {
+            return None;
+        }
+
+        let k = k.min(n);
+        // best[j]: least fuel for positions 0..j in the groups so far, and
+        // split[g][j] where the last of g + 1 groups starts.
+        let mut best = vec![usize::MAX; n + 1];
+        best[0] = 0;
+        let mut split = vec![vec![0u32; n + 1]; k];
+        for (g, split) in split.iter_mut().enumerate() {
+            let mut next = vec![usize::MAX; n + 1];
+            runs.fill_row(&best, &mut next, split, (g + 1, n), (g, n - 1));
+            best = next;
+        }
+
+        let mut targets = vec![];
+        let mut j = n;
+        for g in (0..k).rev() {
+            let i = split[g][j] as usize;
+            targets.push(runs.positions[runs.median(i, j)]);
+            j = i;
+        }
+        targets.reverse();
+        Some((targets, best[n]))
+    }
+}
+
+/// Sorted crab positions with prefix sums, to price any run of them.
+struct Runs {
+    positions: Vec<usize>,
+    crabs: Vec<usize>,
+    sums: Vec<usize>,
+}
+
+impl Runs {
+    fn new(swarm: &CrabSwarm) -> Runs {
+        let mut runs = Runs {
+            positions: vec![],
+            crabs: vec![0],
+            sums: vec![0],
+        };
+        for (&p, &v) in &swarm.positions {
+            runs.positions.push(p);
+            runs.crabs.push(runs.crabs.last().unwrap() + v);
+            runs.sums.push(runs.sums.last().unwrap() + p * v);
+        }
+        runs
+    }
+
+    /// Index of the median of positions `i..j`.
+    fn median(&self, i: usize, j: usize) -> usize {
+        let total = self.crabs[j] - self.crabs[i];
+        i + self.crabs[i + 1..=j].partition_point(|&c| 2 * (c - self.crabs[i]) < total)
+    }
+
+    /// Fuel for the crabs at positions `i..j` to meet at their median.
+    fn cost(&self, i: usize, j: usize) -> usize {
+        let m = self.median(i, j);
+        let p = self.positions[m];
+        let (crabs, sums) = (&self.crabs, &self.sums);
+        p * (crabs[m + 1] - crabs[i]) - (sums[m + 1] - sums[i]) + (sums[j] - sums[m + 1])
+            - p * (crabs[j] - crabs[m + 1])
+    }
+
+    /// Fill `next[j]` for `j` in `js` from `prev`, knowing the best split
+    /// lies in `is`.
+    fn fill_row(
+        &self,
+        prev: &[usize],
+        next: &mut [usize],
+        split: &mut [u32],
+        js: (usize, usize),
+        is: (usize, usize),
+    ) {
+        if js.0 > js.1 {
+            return;
+        }
+        let j = (js.0 + js.1) / 2;
+        let mut best_i = is.0;
+        let last = is.1.min(j - 1);
+        for (i, &before) in prev.iter().enumerate().take(last + 1).skip(is.0) {
+            if before == usize::MAX {
+                continue;
+            }
+            let fuel = before + self.cost(i, j);
+            if fuel < next[j] {
+                next[j] = fuel;
+                best_i = i;
+            }
+        }
+        split[j] = best_i as u32;
+        if j > js.0 {
+            self.fill_row(prev, next, split, (js.0, j - 1), (is.0, best_i));
+        }
+        self.fill_row(prev, next, split, (j + 1, js.1), (best_i, is.1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CrabCloud, Metric};
+    use crate::quest::align_crabs::CrabSwarm;
+
+    #[test]
+    fn test_manhattan() {
+        let cloud = CrabCloud::new("0,0\n4,0\n0,4\n").expect("parse error");
+        let meeting = cloud.best_alignment(Metric::Manhattan).unwrap();
+        assert_eq!(vec![0.0, 0.0], meeting.target);
+        assert_eq!(vec![0, 1, 2], meeting.crabs);
+        assert_eq!(8.0, meeting.fuel);
+
+        let cloud = CrabCloud::new("0,0,0\n2,2,2\n1,5,1\n").expect("parse error");
+        let meeting = cloud.best_alignment(Metric::Manhattan).unwrap();
+        assert_eq!(vec![1.0, 2.0, 1.0], meeting.target);
+        assert_eq!(4.0 + 2.0 + 3.0, meeting.fuel);
+
+        assert!(CrabCloud::new("0,0\n1\n").is_err());
+        assert!(CrabCloud::new("0,x\n").is_err());
+    }
+
+    #[test]
+    fn test_euclidean() {
+        let cloud = CrabCloud::new("0,0\n2,0\n0,2\n2,2\n6,6\n").expect("parse error");
+        let meeting = cloud.best_alignment(Metric::Euclidean).unwrap();
+        // Any point off the optimum costs more.
+        for (dx, dy) in [(0.01, 0.0), (-0.01, 0.0), (0.0, 0.01), (0.0, -0.01)] {
+            let moved = [meeting.target[0] + dx, meeting.target[1] + dy];
+            let fuel: f64 = [[0.0, 0.0], [2.0, 0.0], [0.0, 2.0], [2.0, 2.0], [6.0, 6.0]]
+                .iter()
+                .map(|crab| Metric::Euclidean.distance(crab, &moved))
+                .sum();
+            assert!(fuel > meeting.fuel);
+        }
+        assert_eq!(5.0, Metric::Euclidean.distance(&[0.0, 0.0], &[3.0, 4.0]));
+    }
+
+    #[test]
+    fn test_k_targets() {
+        let cloud =
+            CrabCloud::new("0,0\n1,0\n0,1\n100,100\n101,100\n100,101\n").expect("parse error");
+        let meetings = cloud.best_k_alignment(2, Metric::Manhattan);
+        assert_eq!(2, meetings.len());
+        assert_eq!(vec![0.0, 0.0], meetings[0].target);
+        assert_eq!(vec![100.0, 100.0], meetings[1].target);
+        assert_eq!(vec![3, 4, 5], meetings[1].crabs);
+        assert_eq!(4.0, meetings.iter().map(|m| m.fuel).sum::<f64>());
+        assert!(cloud.best_k_alignment(0, Metric::Manhattan).is_empty());
+    }
+
+    #[test]
+    fn test_k_median_1d() {
+        let crab_swarm = CrabSwarm::new("16,1,2,0,4,2,7,1,2,14").expect("parse error");
+        assert_eq!(Some((vec![2], 37)), crab_swarm.best_k_alignment(1));
+        assert_eq!(Some((vec![2, 14], 11 + 2)), crab_swarm.best_k_alignment(2));
+        assert_eq!(0, crab_swarm.best_k_alignment(10).unwrap().1);
+        assert_eq!(None, crab_swarm.best_k_alignment(0));
+    }
+
+    #[test]
+    fn test_k_median_matches_brute_force() {
+        // Every split of the sorted positions into three runs.
+        let crab_swarm = CrabSwarm::new("1,3,3,8,9,15,20,21,21,22,40,41").expect("parse error");
+        let positions: [usize; 10] = [1, 3, 8, 9, 15, 20, 21, 22, 40, 41];
+        let weights = [1, 2, 1, 1, 1, 1, 2, 1, 1, 1];
+        let run = |i: usize, j: usize| {
+            (i..j)
+                .map(|t| {
+                    (i..j)
+                        .map(|m| weights[m] * positions[m].abs_diff(positions[t]))
+                        .sum::<usize>()
+                })
+                .min()
+                .unwrap()
+        };
+        let mut best = usize::MAX;
+        for a in 1..positions.len() {
+            for b in a + 1..positions.len() {
+                best = best.min(run(0, a) + run(a, b) + run(b, positions.len()));
+            }
+        }
+        assert_eq!(best, crab_swarm.best_k_alignment(3).unwrap().1);
+    }
+}
@@ -0,0 +1,189 @@
+use super::{crab_engine_fuel_cost, CrabSwarm};
+
+/// Fuel for one crab to move between two positions. Costs are expected
+/// not to shrink as a crab moves further in either direction, so targets
+/// outside the swarm never need checking.
+pub trait FuelCost {
+    fn cost(&self, from: usize, to: usize) -> usize;
+
+    /// Whether the total fuel is convex in the target, letting the
+    /// optimizer bisect instead of scanning every position.
+    fn is_convex(&self) -> bool {
+        false
+    }
+}
+
+/// One unit of fuel per step.
+pub struct Linear;
+
+/// Each step costs one more than the previous one.
+pub struct CrabEngine;
+
+/// Fuel grows with the square of the distance.
+pub struct Quadratic;
+
+/// Another cost, never above `cap`.
+pub struct Capped<C: FuelCost> {
+    pub cost: C,
+    pub cap: usize,
+}
+
+/// Fuel per step, depending on the direction.
+pub struct Asymmetric {
+    pub left: usize,
+    pub right: usize,
+}
+
+impl FuelCost for Linear {
+    fn cost(&self, from: usize, to: usize) -> usize {
+        from.abs_diff(to)
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+impl FuelCost for CrabEngine {
+    fn cost(&self, from: usize, to: usize) -> usize {
+        crab_engine_fuel_cost(from.abs_diff(to))
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+impl FuelCost for Quadratic {
+    fn cost(&self, from: usize, to: usize) -> usize {
+        from.abs_diff(to).pow(2)
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+impl<C: FuelCost> FuelCost for Capped<C> {
+    fn cost(&self, from: usize, to: usize) -> usize {
+        self.cost.cost(from, to).min(self.cap)
+    }
+}
+
+impl FuelCost for Asymmetric {
+    fn cost(&self, from: usize, to: usize) -> usize {
+        if to < from {
+            (from - to) * self.left
+        } else {
+            (to - from) * self.right
+        }
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+impl CrabSwarm {
+    pub fn fuel_with(&self, target: usize, cost: &dyn FuelCost) -> usize {
+        self.positions
+            .iter()
+            .map(|(&p, &v)| v * cost.cost(p, target))
+            .sum()
+    }
+
+    pub fn best_alignment_with(&self, cost: &dyn FuelCost) -> usize {
+        CrabSwarm::best_mixed_alignment(&[(self, cost)])
+    }
+
+    /// Least fuel for several kinds of crabs, each with its own cost, to
+    /// meet at one position.
+    pub fn best_mixed_alignment(groups: &[(&CrabSwarm, &dyn FuelCost)]) -> usize {
+        match best_target(groups) {
+            Some(target) => mixed_fuel(groups, target),
+            None => 0,
+        }
+    }
+}
+
+pub(super) fn mixed_fuel(groups: &[(&CrabSwarm, &dyn FuelCost)], target: usize) -> usize {
+    groups
+        .iter()
+        .map(|(swarm, cost)| swarm.fuel_with(target, *cost))
+        .sum()
+}
+
+/// Leftmost and rightmost crab over all groups.
+pub(super) fn span(groups: &[(&CrabSwarm, &dyn FuelCost)]) -> Option<(usize, usize)> {
+    let positions = groups.iter().flat_map(|(swarm, _)| swarm.positions.keys());
+    Some((*positions.clone().min()?, *positions.max()?))
+}
+
+/// Leftmost target with the least fuel.
+pub(super) fn best_target(groups: &[(&CrabSwarm, &dyn FuelCost)]) -> Option<usize> {
+    let (left, right) = span(groups)?;
+    let fuel = |target| mixed_fuel(groups, target);
+    if groups.iter().all(|(_, cost)| cost.is_convex()) {
+        let (mut lo, mut hi) = (left, right);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if fuel(mid) <= fuel(mid + 1) {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        Some(lo)
+    } else {
+        (left..=right).min_by_key(|&target| fuel(target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Asymmetric, Capped, CrabEngine, FuelCost, Linear, Quadratic};
+    use crate::quest::align_crabs::CrabSwarm;
+
+    #[test]
+    fn test_cost_functions() {
+        let crab_swarm = CrabSwarm::new("16,1,2,0,4,2,7,1,2,14").expect("parse error");
+        assert_eq!(37, crab_swarm.best_alignment_with(&Linear));
+        assert_eq!(168, crab_swarm.best_alignment_with(&CrabEngine));
+        // The mean, 4.9, is the least-squares optimum.
+        assert_eq!(
+            crab_swarm.fuel_with(5, &Quadratic),
+            crab_swarm.best_alignment_with(&Quadratic)
+        );
+        // Meeting at 2, the crabs at 7, 14 and 16 each pay the cap.
+        let capped = Capped {
+            cost: Linear,
+            cap: 5,
+        };
+        assert_eq!(6 + 3 * 5, crab_swarm.best_alignment_with(&capped));
+        assert_eq!(5, capped.cost(16, 2));
+    }
+
+    #[test]
+    fn test_asymmetric() {
+        let crab_swarm = CrabSwarm::new("0,10").expect("parse error");
+        let uphill = Asymmetric { left: 1, right: 3 };
+        assert_eq!(30, uphill.cost(0, 10));
+        assert_eq!(10, uphill.cost(10, 0));
+        assert_eq!(10, crab_swarm.best_alignment_with(&uphill));
+    }
+
+    #[test]
+    fn test_mixed_alignment() {
+        let walkers = CrabSwarm::new("0,0,0").expect("parse error");
+        let swimmers = CrabSwarm::new("10").expect("parse error");
+        assert_eq!(
+            10,
+            CrabSwarm::best_mixed_alignment(&[(&walkers, &Linear), (&swimmers, &Linear)])
+        );
+        // Moving the swimmer all the way costs 55, the walkers meet it at 7.
+        assert_eq!(
+            3 * 7 + 6,
+            CrabSwarm::best_mixed_alignment(&[(&walkers, &Linear), (&swimmers, &CrabEngine)])
+        );
+    }
+}
@@ -1,134 +1,125 @@
+pub mod encoder;
+pub mod glyphs;
+pub mod note;
+pub mod render;
+pub mod solver;
+
 use std::{
-    collections::{BTreeMap, BTreeSet},
     fs::File,
-    io::{self, BufRead, BufReader, Read},
+    io::{self, Read},
 };
 
+use glyphs::GlyphSet;
+use note::DisplayNote;
+use solver::Wiring;
+
+use crate::invalid;
+
+/// Segments of a standard display, and those lit for each digit.
+const SEGMENTS: &str = "abcdefg";
+const DIGITS: [&str; 10] = [
+    "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+];
+
 pub struct SevenSegments;
 
 impl SevenSegments {
-    pub fn input_from_file(path: &str) -> io::Result<Vec<String>> {
+    pub fn input_from_file(path: &str) -> io::Result<Vec<DisplayNote>> {
         let mut file = File::open(path)?;
         let mut buf = String::new();
         file.read_to_string(&mut buf)?;
         SevenSegments::input_from_str(&buf)
     }
 
-    pub fn input_from_str(input: &str) -> io::Result<Vec<String>> {
-        let reader = BufReader::new(input.as_bytes());
-        Ok(reader.lines().map(|l| l.unwrap()).collect())
+    /// One note per non-blank line.
+    pub fn input_from_str(input: &str) -> io::Result<Vec<DisplayNote>> {
+        SevenSegments::input_from_str_with(input, &GlyphSet::standard())
     }
 
-    pub fn count_1478(input: &[String]) -> usize {
-        let mut counter = 0;
-        for line in input {
-            let right = line.split('|').nth(1).unwrap().trim();
-            for digit in right.split(' ') {
-                match digit.len() {
-                    2 | 4 | 3 | 7 => counter += 1,
-                    _ => (),
-                }
-            }
-        }
-        counter
+    /// Like `input_from_str`, for notes on a display showing `glyphs`.
+    pub fn input_from_str_with(input: &str, glyphs: &GlyphSet) -> io::Result<Vec<DisplayNote>> {
+        input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(n, line)| {
+                DisplayNote::parse_with(line, glyphs)
+                    .map_err(|e| invalid(format!("line {}: {}", n + 1, e)))
+            })
+            .collect()
     }
 
-    pub fn decode_display(input: &[String]) -> usize {
-        let mut result = 0;
-        for line in input {
-            let (left, right) = line.split_once('|').unwrap();
-            let mapping = find_mapping(left.trim());
-            let display = to_4digit_number(&mapping, &mut right.trim().split(' '));
-            result += display;
-        }
-        result
+    pub fn count_1478(input: &[DisplayNote]) -> usize {
+        SevenSegments::count_unique(input, &GlyphSet::standard())
     }
-}
 
-fn find_mapping(input: &str) -> BTreeMap<BTreeSet<char>, u8> {
-    let eight = BTreeSet::from(['a', 'b', 'c', 'd', 'e', 'f', 'g']);
-    let mut mapping = BTreeMap::new();
-    let mut segments = BTreeMap::new();
-    for seg in ['a', 'b', 'c', 'd', 'e', 'f', 'g'] {
-        segments.insert(seg, eight.clone());
+    /// Output patterns whose length alone tells which glyph they show.
+    pub fn count_unique(input: &[DisplayNote], glyphs: &GlyphSet) -> usize {
+        let lengths = glyphs.unique_lengths();
+        input
+            .iter()
+            .flat_map(|note| &note.outputs)
+            .filter(|digit| lengths.contains_key(&digit.len()))
+            .count()
     }
-    let observations: Vec<_> = input
-        .split(' ')
-        .filter(|s| !s.is_empty())
-        .map(|s| s.chars().collect::<BTreeSet<char>>())
-        .collect();
-
-    for digit in &observations {
-        match digit.len() {
-            2 => {
-                mapping.insert(1, digit.clone());
-            }
-            4 => {
-                mapping.insert(4, digit.clone());
-            }
-            3 => {
-                mapping.insert(7, digit.clone());
-            }
-            7 => {
-                mapping.insert(8, digit.clone());
-            }
-            _ => (),
-        }
+
+    pub fn decode_display(input: &[DisplayNote]) -> io::Result<usize> {
+        SevenSegments::decode_display_with(input, &GlyphSet::standard())
     }
 
-    let one = mapping[&1].clone();
-    let four = mapping[&4].clone();
-    for digit in &observations {
-        match (
-            digit.len(),
-            digit.intersection(&one).count(),
-            digit.intersection(&four).count(),
-        ) {
-            (5, 1, 2) => {
-                mapping.insert(2, digit.clone());
-            }
-            (5, 2, 3) => {
-                mapping.insert(3, digit.clone());
-            }
-            (5, 1, 3) => {
-                mapping.insert(5, digit.clone());
-            }
-            (6, 2, 3) => {
-                mapping.insert(0, digit.clone());
-            }
-            (6, 1, 3) => {
-                mapping.insert(6, digit.clone());
-            }
-            (6, 2, 4) => {
-                mapping.insert(9, digit.clone());
-            }
-            _ => (),
+    /// Sum of the displays, failing on the first note that does not decode.
+    pub fn decode_display_with(input: &[DisplayNote], glyphs: &GlyphSet) -> io::Result<usize> {
+        let mut result = 0;
+        for (n, decoded) in SevenSegments::decode_each(input, glyphs)
+            .into_iter()
+            .enumerate()
+        {
+            result += decoded.map_err(|e| invalid(format!("note {}: {}", n + 1, e)))?;
         }
+        Ok(result)
     }
 
-    mapping.into_iter().map(|(k, v)| (v, k)).collect()
-}
-
-fn lookup(mapping: &BTreeMap<BTreeSet<char>, u8>, input: &str) -> u8 {
-    let input = input.chars().collect::<BTreeSet<_>>();
-    mapping[&input]
+    /// Every note's number, or why it could not be read.
+    pub fn decode_each(input: &[DisplayNote], glyphs: &GlyphSet) -> Vec<io::Result<usize>> {
+        input.iter().map(|note| note.decode_with(glyphs)).collect()
+    }
 }
 
-fn to_4digit_number(
-    mapping: &BTreeMap<BTreeSet<char>, u8>,
+/// Read the patterns as one number, each glyph worth its place in the set
+/// and the glyph count as the base.
+fn to_number(
+    wiring: &Wiring,
+    glyphs: &GlyphSet,
     input: &mut dyn Iterator<Item = &str>,
-) -> usize {
-    let mut result = 0;
-    for digit in input {
-        result *= 10;
-        result += lookup(mapping, digit) as usize;
+) -> io::Result<usize> {
+    let radix = glyphs.glyphs().len();
+    if radix < 2 {
+        return Err(invalid(format!(
+            "cannot read numbers with {} glyphs",
+            radix
+        )));
     }
-    result
+    let mut result: usize = 0;
+    for pattern in input {
+        let value = wiring
+            .label(pattern, glyphs)
+            .and_then(|label| glyphs.value(label))
+            .ok_or_else(|| invalid(format!("unknown output pattern {:?}", pattern)))?;
+        result = result
+            .checked_mul(radix)
+            .and_then(|r| r.checked_add(value))
+            .ok_or_else(|| invalid("number too large".to_string()))?;
+    }
+    Ok(result)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{find_mapping, lookup, to_4digit_number, SevenSegments};
+    use super::{
+        glyphs::GlyphSet,
+        solver::{solve, Solution, Wiring},
+        to_number, SevenSegments, SEGMENTS,
+    };
 
     #[test]
     fn test_count_1478() {
@@ -150,20 +141,28 @@ gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc |fgae cfgab fg bagce"
 
     #[test]
     fn test_mapping() {
-        let mapping = find_mapping("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab");
-        assert_eq!(8, lookup(&mapping, "acedgfb"));
-        assert_eq!(5, lookup(&mapping, "cdfbe"));
-        assert_eq!(2, lookup(&mapping, "gcdfa"));
-        assert_eq!(3, lookup(&mapping, "fbcad"));
-        assert_eq!(7, lookup(&mapping, "dab"));
-        assert_eq!(9, lookup(&mapping, "cefabd"));
-        assert_eq!(6, lookup(&mapping, "cdfgeb"));
-        assert_eq!(4, lookup(&mapping, "eafb"));
-        assert_eq!(0, lookup(&mapping, "cagedb"));
-        assert_eq!(1, lookup(&mapping, "ab"));
+        let patterns = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab";
+        let Solution::Unique(mapping) = solve(&patterns.split(' ').collect::<Vec<_>>()) else {
+            panic!("expected a unique wiring");
+        };
+        assert_eq!(Some(8), mapping.digit("acedgfb"));
+        assert_eq!(Some(5), mapping.digit("cdfbe"));
+        assert_eq!(Some(2), mapping.digit("gcdfa"));
+        assert_eq!(Some(3), mapping.digit("fbcad"));
+        assert_eq!(Some(7), mapping.digit("dab"));
+        assert_eq!(Some(9), mapping.digit("cefabd"));
+        assert_eq!(Some(6), mapping.digit("cdfgeb"));
+        assert_eq!(Some(4), mapping.digit("eafb"));
+        assert_eq!(Some(0), mapping.digit("cagedb"));
+        assert_eq!(Some(1), mapping.digit("ab"));
         assert_eq!(
             5353,
-            to_4digit_number(&mapping, &mut "cdfeb fcadb cdfeb cdbaf".split(' '))
+            to_number(
+                &mapping,
+                &GlyphSet::standard(),
+                &mut "cdfeb fcadb cdfeb cdbaf".split(' ')
+            )
+            .unwrap()
         );
     }
 
@@ -182,6 +181,65 @@ egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg |gbdfcae bgc cg cgb
 gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc |fgae cfgab fg bagce",
         )
         .expect("parse error");
-        assert_eq!(61229, SevenSegments::decode_display(&input));
+        assert_eq!(61229, SevenSegments::decode_display(&input).unwrap());
+        assert!(SevenSegments::input_from_str("ab dab ab").is_err());
+        assert_eq!(
+            "line 3: bad signal pattern \"x1\"",
+            SevenSegments::input_from_str("ab dab | ab\n\nx1 @@ | ?!")
+                .unwrap_err()
+                .to_string()
+        );
+
+        let decoded = SevenSegments::decode_each(&input, &GlyphSet::standard());
+        assert_eq!(8394, *decoded[0].as_ref().unwrap());
+        assert_eq!(4315, *decoded[9].as_ref().unwrap());
+    }
+
+    #[test]
+    fn test_glyph_values() {
+        // Labels need not be digits: every pair and triple of segments,
+        // labelled from U+0100 on, makes a base-56 display.
+        let pairs_and_triples = (0..128u32)
+            .filter(|bits| matches!(bits.count_ones(), 2 | 3))
+            .map(|bits| {
+                SEGMENTS
+                    .chars()
+                    .enumerate()
+                    .filter(|&(i, _)| bits >> i & 1 == 1)
+                    .map(|(_, s)| s)
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        let glyphs = pairs_and_triples
+            .iter()
+            .enumerate()
+            .map(|(i, lit)| (char::from_u32(0x100 + i as u32).unwrap(), lit.as_str()))
+            .collect::<Vec<_>>();
+        let glyphs = GlyphSet::new(SEGMENTS, &glyphs).unwrap();
+        assert_eq!(56, glyphs.glyphs().len());
+        let wiring = Wiring::from_permutation(SEGMENTS).unwrap();
+        assert_eq!(
+            56,
+            to_number(&wiring, &glyphs, &mut "ac ab".split(' ')).unwrap()
+        );
+        assert!(to_number(&wiring, &glyphs, &mut "abcd".split(' ')).is_err());
+
+        let single = GlyphSet::new(SEGMENTS, &[('x', "ab")]).unwrap();
+        assert!(to_number(&wiring, &single, &mut "ab".split(' ')).is_err());
+    }
+
+    #[test]
+    fn test_hex_display() {
+        let input = SevenSegments::input_from_str(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | deafgb cdfeb eafb",
+        )
+        .expect("parse error");
+        assert_eq!(
+            0xA54,
+            SevenSegments::decode_display_with(&input, &GlyphSet::hex()).unwrap()
+        );
+        assert_eq!(1, SevenSegments::count_1478(&input));
+        assert_eq!(0, SevenSegments::count_unique(&input, &GlyphSet::hex()));
+        assert!(SevenSegments::decode_display(&input).is_err());
     }
 }
@@ -0,0 +1,97 @@
+use std::io;
+
+use super::{solver::Wiring, DIGITS, SEGMENTS};
+use crate::{invalid, rng::Rng};
+
+impl Wiring {
+    /// `wires` names the wire driving each segment, in segment order: on a
+    /// standard display `"deafgbc"` drives segment `a` from wire `d`.
+    pub fn from_permutation(wires: &str) -> io::Result<Wiring> {
+        let mut sorted = wires.chars().collect::<Vec<_>>();
+        sorted.sort_unstable();
+        if sorted.iter().collect::<String>() != SEGMENTS {
+            return Err(invalid(format!(
+                "{:?} is not a permutation of {}",
+                wires, SEGMENTS
+            )));
+        }
+        Ok(Wiring {
+            segments: wires.chars().zip(SEGMENTS.chars()).collect(),
+        })
+    }
+
+    /// Wires lit for a digit, in alphabetical order.
+    pub fn encode_digit(&self, digit: u8) -> Option<String> {
+        let glyph = DIGITS.get(digit as usize)?;
+        let mut wires = self
+            .segments
+            .iter()
+            .filter(|(_, segment)| glyph.contains(**segment))
+            .map(|(&wire, _)| wire)
+            .collect::<Vec<_>>();
+        wires.sort_unstable();
+        Some(wires.into_iter().collect())
+    }
+
+    /// One pattern per decimal digit of `number`.
+    pub fn encode(&self, number: usize) -> Vec<String> {
+        number
+            .to_string()
+            .bytes()
+            .map(|b| self.encode_digit(b - b'0').unwrap())
+            .collect()
+    }
+
+    /// A note line in the puzzle format: the ten digits scrambled and
+    /// shuffled by `seed`, then `number` padded with zeros to `digits`
+    /// outputs.
+    pub fn encode_note(&self, number: usize, digits: usize, seed: u64) -> String {
+        let mut patterns = (0..10)
+            .map(|d| self.encode_digit(d).unwrap())
+            .collect::<Vec<_>>();
+        Rng::new(seed).shuffle(&mut patterns);
+        let outputs = format!("{:0width$}", number, width = digits)
+            .bytes()
+            .map(|b| self.encode_digit(b - b'0').unwrap())
+            .collect::<Vec<_>>();
+        format!("{} | {}", patterns.join(" "), outputs.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::quest::seven_segments::{note::DisplayNote, solver::Wiring};
+
+    #[test]
+    fn test_encode() {
+        let wiring = Wiring::from_permutation("deafgbc").unwrap();
+        assert_eq!(Some("ab".to_string()), wiring.encode_digit(1));
+        assert_eq!(Some("abcdf".to_string()), wiring.encode_digit(3));
+        assert_eq!(None, wiring.encode_digit(10));
+        assert_eq!(
+            vec!["bcdef", "abcdf", "bcdef", "abcdf"],
+            wiring.encode(5353)
+        );
+        assert_eq!(Some(5), wiring.digit(&wiring.encode(5)[0]));
+        assert!(Wiring::from_permutation("deafgbb").is_err());
+        assert!(Wiring::from_permutation("deafgb").is_err());
+    }
+
+    #[test]
+    fn test_encode_note_roundtrip() {
+        let wiring = Wiring::from_permutation("gfedcba").unwrap();
+        let note = wiring.encode_note(42, 4, 7);
+        assert!(note.ends_with("| abcefg abcefg bdef acdeg"));
+        assert_eq!(note, wiring.encode_note(42, 4, 7));
+        assert_ne!(note, wiring.encode_note(42, 4, 8));
+        let note = DisplayNote::parse(&note).unwrap();
+        // Patterns no longer come in digit order.
+        assert_ne!(
+            (0..10)
+                .map(|d| wiring.encode_digit(d).unwrap())
+                .collect::<Vec<_>>(),
+            note.patterns
+        );
+        assert_eq!(42, note.decode().unwrap());
+    }
+}
@@ -0,0 +1,194 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::{self, Read},
+};
+
+use super::{DIGITS, SEGMENTS};
+use crate::invalid;
+
+/// Segments of a display and the glyphs it can show, each a label with
+/// the set of segments it lights.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GlyphSet {
+    segments: BTreeSet<char>,
+    glyphs: Vec<(char, BTreeSet<char>)>,
+}
+
+impl GlyphSet {
+    pub fn new(segments: &str, glyphs: &[(char, &str)]) -> io::Result<GlyphSet> {
+        let segments = segments.chars().collect::<BTreeSet<_>>();
+        let mut set = GlyphSet {
+            segments,
+            glyphs: vec![],
+        };
+        for &(label, lit) in glyphs {
+            let lit = lit.chars().collect::<BTreeSet<_>>();
+            if !lit.is_subset(&set.segments) {
+                return Err(invalid(format!("glyph {} uses unknown segments", label)));
+            }
+            if let Some((other, _)) = set.glyphs.iter().find(|(l, g)| *l == label || *g == lit) {
+                return Err(invalid(format!("glyph {} clashes with {}", label, other)));
+            }
+            set.glyphs.push((label, lit));
+        }
+        Ok(set)
+    }
+
+    /// Digits 0–9 on a seven-segment display.
+    pub fn standard() -> GlyphSet {
+        GlyphSet::new(SEGMENTS, &digits()).unwrap()
+    }
+
+    /// Digits 0–9 then `A b C d E F`, labelled `A`–`F`.
+    pub fn hex() -> GlyphSet {
+        let mut glyphs = digits();
+        glyphs.extend([
+            ('A', "abcdef"),
+            ('B', "bdefg"),
+            ('C', "abeg"),
+            ('D', "cdefg"),
+            ('E', "abdeg"),
+            ('F', "abde"),
+        ]);
+        GlyphSet::new(SEGMENTS, &glyphs).unwrap()
+    }
+
+    pub fn from_file(path: &str) -> io::Result<GlyphSet> {
+        let mut file = File::open(path)?;
+        let mut buf = String::new();
+        file.read_to_string(&mut buf)?;
+        GlyphSet::parse(&buf)
+    }
+
+    /// A `segments <names>` line, then one `<label> <lit segments>` line
+    /// per glyph. Blank lines and lines starting with `#` are skipped.
+    pub fn parse(input: &str) -> io::Result<GlyphSet> {
+        let mut lines = input
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'));
+        let segments = lines
+            .next()
+            .and_then(|l| l.strip_prefix("segments "))
+            .ok_or_else(|| invalid("missing segments line".to_string()))?;
+        let mut glyphs = vec![];
+        for line in lines {
+            let mut parts = line.split_whitespace();
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(label), Some(lit), None) if label.chars().count() == 1 => {
+                    glyphs.push((label.chars().next().unwrap(), lit))
+                }
+                _ => return Err(invalid(format!("malformed glyph line {:?}", line))),
+            }
+        }
+        GlyphSet::new(segments.trim(), &glyphs)
+    }
+
+    pub fn segments(&self) -> &BTreeSet<char> {
+        &self.segments
+    }
+
+    pub fn glyphs(&self) -> &[(char, BTreeSet<char>)] {
+        &self.glyphs
+    }
+
+    pub fn label(&self, lit: &BTreeSet<char>) -> Option<char> {
+        self.glyphs
+            .iter()
+            .find(|(_, glyph)| glyph == lit)
+            .map(|&(label, _)| label)
+    }
+
+    /// What a glyph counts for in a number: its place in the set.
+    pub fn value(&self, label: char) -> Option<usize> {
+        self.glyphs.iter().position(|&(l, _)| l == label)
+    }
+
+    pub fn glyph(&self, label: char) -> Option<&BTreeSet<char>> {
+        self.glyphs
+            .iter()
+            .find(|&&(l, _)| l == label)
+            .map(|(_, glyph)| glyph)
+    }
+
+    /// Glyphs told apart by their segment count alone, keyed by that count.
+    pub fn unique_lengths(&self) -> BTreeMap<usize, char> {
+        let mut by_length: BTreeMap<usize, Vec<char>> = BTreeMap::new();
+        for (label, glyph) in &self.glyphs {
+            by_length.entry(glyph.len()).or_default().push(*label);
+        }
+        by_length
+            .into_iter()
+            .filter(|(_, labels)| labels.len() == 1)
+            .map(|(len, labels)| (len, labels[0]))
+            .collect()
+    }
+}
+
+/// Labels `0`–`9` with the segments each lights.
+fn digits() -> Vec<(char, &'static str)> {
+    DIGITS
+        .iter()
+        .enumerate()
+        .map(|(d, &lit)| (char::from(b'0' + d as u8), lit))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::GlyphSet;
+    use crate::quest::seven_segments::solver::{solve_with, Solution};
+
+    #[test]
+    fn test_unique_lengths() {
+        assert_eq!(
+            BTreeMap::from([(2, '1'), (3, '7'), (4, '4'), (7, '8')]),
+            GlyphSet::standard().unique_lengths()
+        );
+        // C and F join 4 at four segments, leaving 1, 7 and 8.
+        assert_eq!(
+            BTreeMap::from([(2, '1'), (3, '7'), (7, '8')]),
+            GlyphSet::hex().unique_lengths()
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let font = "# a display of three bars
+            segments tmb
+            0 tb
+            1 m
+            2 tmb
+            3 t";
+        let glyphs = GlyphSet::parse(font).unwrap();
+        assert_eq!(4, glyphs.glyphs().len());
+        assert_eq!(Some('2'), glyphs.label(&"tmb".chars().collect()));
+        assert!(GlyphSet::parse("0 tb").is_err());
+        assert!(GlyphSet::parse("segments tmb\n0 tx").is_err());
+        assert!(GlyphSet::parse("segments tmb\n0 tb\n1 bt").is_err());
+        assert!(GlyphSet::parse("segments tmb\n0 tb m").is_err());
+
+        // Wires m and b are crossed; 3 tells which of the two-bar wires
+        // drives the top.
+        match solve_with(&["mt", "b", "tmb", "t"], &glyphs) {
+            Solution::Unique(wiring) => assert_eq!("b>m m>b t>t", wiring.to_string()),
+            other => panic!("expected a unique wiring, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_hex_display() {
+        let hex = GlyphSet::hex();
+        let patterns = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab";
+        let Solution::Unique(wiring) = solve_with(&patterns.split(' ').collect::<Vec<_>>(), &hex)
+        else {
+            panic!("expected a unique wiring");
+        };
+        // The wires for segments a b c d e f light an A.
+        assert_eq!(Some('A'), wiring.label("deafgb", &hex));
+        assert_eq!(Some('5'), wiring.label("cdfeb", &hex));
+    }
+}
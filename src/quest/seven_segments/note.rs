@@ -0,0 +1,198 @@
+use std::io;
+
+use super::{
+    glyphs::GlyphSet,
+    solver::{solve_with, Solution, MAX_WIRINGS},
+    to_number,
+};
+use crate::invalid;
+
+/// One line of notes: scrambled patterns of the glyphs, ten of them for a
+/// standard display, then the patterns shown on the display.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DisplayNote {
+    pub patterns: Vec<String>,
+    pub outputs: Vec<String>,
+}
+
+impl DisplayNote {
+    /// A note for a standard display.
+    pub fn parse(line: &str) -> io::Result<DisplayNote> {
+        DisplayNote::parse_with(line, &GlyphSet::standard())
+    }
+
+    /// A note whose patterns use the segments of `glyphs`, at most one per
+    /// glyph.
+    pub fn parse_with(line: &str, glyphs: &GlyphSet) -> io::Result<DisplayNote> {
+        let (left, right) = line
+            .split_once('|')
+            .ok_or_else(|| invalid("missing '|'".to_string()))?;
+        let patterns = split_patterns(left);
+        if patterns.is_empty() {
+            return Err(invalid("no signal patterns".to_string()));
+        }
+        let outputs = split_patterns(right);
+        if outputs.is_empty() {
+            return Err(invalid("no output digits".to_string()));
+        }
+        let note = DisplayNote { patterns, outputs };
+        note.check(glyphs)?;
+        Ok(note)
+    }
+
+    fn check(&self, glyphs: &GlyphSet) -> io::Result<()> {
+        if self.patterns.len() > glyphs.glyphs().len() {
+            return Err(invalid(format!(
+                "{} signal patterns for {} glyphs",
+                self.patterns.len(),
+                glyphs.glyphs().len()
+            )));
+        }
+        match self
+            .patterns
+            .iter()
+            .chain(&self.outputs)
+            .find(|p| !p.chars().all(|c| glyphs.segments().contains(&c)))
+        {
+            Some(pattern) => Err(invalid(format!("bad signal pattern {:?}", pattern))),
+            None => Ok(()),
+        }
+    }
+
+    pub fn decode(&self) -> io::Result<usize> {
+        self.decode_with(&GlyphSet::standard())
+    }
+
+    /// The displayed number, each glyph worth its place in the set and the
+    /// glyph count as the base: base 16 for the hex set. Output patterns
+    /// constrain the wiring too, and several wirings are fine as long as
+    /// they all read the outputs the same way.
+    pub fn decode_with(&self, glyphs: &GlyphSet) -> io::Result<usize> {
+        self.check(glyphs)?;
+        let signals = self
+            .patterns
+            .iter()
+            .chain(&self.outputs)
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        let wirings = match solve_with(&signals, glyphs) {
+            Solution::Unique(wiring) => vec![wiring],
+            Solution::Ambiguous(wirings) => wirings,
+            Solution::Contradiction => {
+                return Err(invalid("no wiring fits the patterns".to_string()))
+            }
+        };
+        let mut numbers = wirings
+            .iter()
+            .map(|wiring| to_number(wiring, glyphs, &mut self.outputs.iter().map(String::as_str)));
+        let number = numbers.next().unwrap()?;
+        for other in numbers {
+            if other? != number {
+                return Err(invalid(format!(
+                    "{} wirings fit the patterns and read the outputs differently",
+                    wirings.len()
+                )));
+            }
+        }
+        // Wirings past the search limit might still disagree.
+        if wirings.len() >= MAX_WIRINGS {
+            return Err(invalid(format!(
+                "at least {} wirings fit the patterns",
+                MAX_WIRINGS
+            )));
+        }
+        Ok(number)
+    }
+}
+
+fn split_patterns(input: &str) -> Vec<String> {
+    input.split_whitespace().map(String::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DisplayNote;
+    use crate::quest::seven_segments::glyphs::GlyphSet;
+
+    #[test]
+    fn test_parse() {
+        let note = DisplayNote::parse(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb",
+        )
+        .unwrap();
+        assert_eq!(10, note.patterns.len());
+        assert_eq!(vec!["cdfeb", "fcadb"], note.outputs);
+        assert_eq!(53, note.decode().unwrap());
+
+        assert!(DisplayNote::parse("acedgfb cdfbe").is_err());
+        assert!(DisplayNote::parse(" | ab").is_err());
+        assert!(
+            DisplayNote::parse("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab |")
+                .is_err()
+        );
+
+        // Fewer patterns are fine while every wiring left reads the same.
+        let note = DisplayNote::parse("ab dab | ab").unwrap();
+        assert_eq!(1, note.decode().unwrap());
+        // Pattern count and segments are checked against the glyph set.
+        assert_eq!(
+            "bad signal pattern \"x1\"",
+            DisplayNote::parse("x1 @@ | ?!").unwrap_err().to_string()
+        );
+        let line = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab ab | ab";
+        assert_eq!(
+            "11 signal patterns for 10 glyphs",
+            DisplayNote::parse(line).unwrap_err().to_string()
+        );
+        let note = DisplayNote::parse_with(line, &GlyphSet::hex()).unwrap();
+        let bars = GlyphSet::parse("segments tmb\n0 tb\n1 m").unwrap();
+        assert_eq!(
+            "11 signal patterns for 2 glyphs",
+            note.decode_with(&bars).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_long_display() {
+        let patterns = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab";
+        let note = DisplayNote::parse(&format!("{} | {}", patterns, "ab ".repeat(12))).unwrap();
+        assert_eq!(111_111_111_111, note.decode().unwrap());
+        let note = DisplayNote::parse(&format!("{} | {}", patterns, "ab ".repeat(30))).unwrap();
+        assert!(note.decode().is_err());
+        let note = DisplayNote::parse(&format!("{} | abc", patterns)).unwrap();
+        assert!(note.decode().is_err());
+    }
+
+    #[test]
+    fn test_ambiguous_wirings() {
+        // Without 1 and 7 the top and bottom wires can swap, but no output
+        // here tells them apart.
+        let patterns = "acedgfb cdfbe gcdfa fbcad cefabd cdfgeb eafb cagedb";
+        let note = DisplayNote {
+            patterns: patterns.split(' ').map(String::from).collect(),
+            outputs: vec!["cdfeb".to_string(), "fcadb".to_string()],
+        };
+        assert_eq!(53, note.decode().unwrap());
+        // An output of 7 is a constraint of its own and settles the wiring.
+        let note = DisplayNote {
+            outputs: vec!["dab".to_string()],
+            ..note
+        };
+        assert_eq!(7, note.decode().unwrap());
+
+        let note = DisplayNote {
+            patterns: vec!["ab".to_string(), "dab".to_string()],
+            outputs: vec!["cdfbe".to_string()],
+        };
+        let error = note.decode().unwrap_err().to_string();
+        assert!(error.contains("read the outputs differently"), "{}", error);
+        let note = DisplayNote {
+            patterns: vec!["abcdefg".to_string()],
+            outputs: vec!["abcdefg".to_string()],
+        };
+        assert_eq!(
+            "at least 100 wirings fit the patterns",
+            note.decode().unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::BTreeSet;
+
+use super::{SevenSegments, DIGITS};
+
+impl SevenSegments {
+    /// Draw digits three characters wide and three lines high:
+    ///
+    /// ```text
+    ///  _     _
+    /// | |  | _|
+    /// |_|  ||_
+    /// ```
+    pub fn render(digits: &[u8]) -> String {
+        let glyphs = digits
+            .iter()
+            .map(|&d| {
+                DIGITS
+                    .get(d as usize)
+                    .map_or_else(BTreeSet::new, |glyph| glyph.chars().collect())
+            })
+            .collect::<Vec<_>>();
+        SevenSegments::render_segments(&glyphs)
+    }
+
+    /// Draw arbitrary sets of lit segments, such as a decoded pattern that
+    /// matches no digit.
+    pub fn render_segments(glyphs: &[BTreeSet<char>]) -> String {
+        let rows = [[' ', 'a', ' '], ['b', 'd', 'c'], ['e', 'g', 'f']];
+        let mut art = String::new();
+        for row in rows {
+            for lit in glyphs {
+                for (col, segment) in row.iter().enumerate() {
+                    art.push(match (lit.contains(segment), col) {
+                        (false, _) => ' ',
+                        (true, 1) => '_',
+                        (true, _) => '|',
+                    });
+                }
+            }
+            art.push('\n');
+        }
+        art
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::quest::seven_segments::{solver::Wiring, SevenSegments};
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            concat!(
+                " _     _  _     _  _  _  _  _ \n",
+                "| |  | _| _||_||_ |_   ||_||_|\n",
+                "|_|  ||_  _|  | _||_|  ||_| _|\n",
+            ),
+            SevenSegments::render(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9])
+        );
+
+        let wiring = Wiring::from_permutation("deafgbc").unwrap();
+        let lit = wiring.translate("cdfeb").unwrap();
+        assert_eq!(
+            SevenSegments::render(&[5]),
+            SevenSegments::render_segments(&[lit])
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use super::glyphs::GlyphSet;
+
+/// Search stops once this many wirings fit the observations.
+pub const MAX_WIRINGS: usize = 100;
+
+/// Which segment each signal wire drives.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Wiring {
+    pub(super) segments: BTreeMap<char, char>,
+}
+
+/// Outcome of solving one display's wiring.
+#[derive(PartialEq, Debug)]
+pub enum Solution {
+    Unique(Wiring),
+    /// Wirings that all fit, at most `MAX_WIRINGS` of them.
+    Ambiguous(Vec<Wiring>),
+    /// No wiring fits: a pattern matches no digit, or patterns conflict.
+    Contradiction,
+}
+
+impl Wiring {
+    pub fn segment(&self, wire: char) -> Option<char> {
+        self.segments.get(&wire).copied()
+    }
+
+    /// Segments lit by a scrambled pattern.
+    pub fn translate(&self, pattern: &str) -> Option<BTreeSet<char>> {
+        pattern.chars().map(|wire| self.segment(wire)).collect()
+    }
+
+    pub fn label(&self, pattern: &str, glyphs: &GlyphSet) -> Option<char> {
+        glyphs.label(&self.translate(pattern)?)
+    }
+
+    /// The digit a pattern shows on a standard display.
+    pub fn digit(&self, pattern: &str) -> Option<u8> {
+        let glyphs = GlyphSet::standard();
+        let label = self.label(pattern, &glyphs)?;
+        glyphs.value(label).map(|d| d as u8)
+    }
+}
+
+impl fmt::Display for Wiring {
+    /// `wire>segment` pairs, e.g. `a>c b>f ...`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pairs = self
+            .segments
+            .iter()
+            .map(|(wire, segment)| format!("{}>{}", wire, segment))
+            .collect::<Vec<_>>();
+        write!(f, "{}", pairs.join(" "))
+    }
+}
+
+/// Find every wiring under which each pattern shows some digit. Patterns
+/// need not cover all ten digits; the fewer there are, the likelier the
+/// result is ambiguous.
+pub fn solve(patterns: &[&str]) -> Solution {
+    solve_with(patterns, &GlyphSet::standard())
+}
+
+/// Like `solve`, for a display showing `glyphs`. Wires share the names of
+/// the segments they drive.
+pub fn solve_with(patterns: &[&str], glyphs: &GlyphSet) -> Solution {
+    let segments = glyphs.segments().clone();
+    let mut observations = vec![];
+    for pattern in patterns {
+        let wires = pattern.chars().collect::<BTreeSet<_>>();
+        if wires.len() != pattern.len() || !wires.is_subset(&segments) {
+            return Solution::Contradiction;
+        }
+        let candidates = glyphs
+            .glyphs()
+            .iter()
+            .map(|(_, glyph)| glyph)
+            .filter(|glyph| glyph.len() == wires.len())
+            .cloned()
+            .collect::<Vec<_>>();
+        observations.push((wires, candidates));
+    }
+
+    // A wire in a pattern drives a segment lit by one of the digits it can
+    // show, a wire outside it one left dark.
+    let mut domains = segments
+        .iter()
+        .map(|&wire| (wire, segments.clone()))
+        .collect::<BTreeMap<_, _>>();
+    for (wires, candidates) in &observations {
+        for (wire, domain) in domains.iter_mut() {
+            let allowed = candidates
+                .iter()
+                .flat_map(|glyph| {
+                    segments
+                        .iter()
+                        .filter(move |s| glyph.contains(s) == wires.contains(wire))
+                })
+                .copied()
+                .collect::<BTreeSet<_>>();
+            domain.retain(|s| allowed.contains(s));
+        }
+    }
+
+    let mut found = vec![];
+    search(&observations, &domains, &mut BTreeMap::new(), &mut found);
+    match found.len() {
+        0 => Solution::Contradiction,
+        1 => Solution::Unique(found.pop().unwrap()),
+        _ => Solution::Ambiguous(found),
+    }
+}
+
+type Observation = (BTreeSet<char>, Vec<BTreeSet<char>>);
+
+/// Assign the wire with the fewest options left, pruning as soon as a
+/// pattern cannot show any of its digits.
+fn search(
+    observations: &[Observation],
+    domains: &BTreeMap<char, BTreeSet<char>>,
+    assigned: &mut BTreeMap<char, char>,
+    found: &mut Vec<Wiring>,
+) {
+    if found.len() >= MAX_WIRINGS {
+        return;
+    }
+    let used = assigned.values().copied().collect::<BTreeSet<_>>();
+    let next = domains
+        .iter()
+        .filter(|(wire, _)| !assigned.contains_key(wire))
+        .map(|(&wire, domain)| (wire, domain.difference(&used).copied().collect::<Vec<_>>()))
+        .min_by_key(|(_, options)| options.len());
+    let Some((wire, options)) = next else {
+        found.push(Wiring {
+            segments: assigned.clone(),
+        });
+        return;
+    };
+    for segment in options {
+        assigned.insert(wire, segment);
+        if consistent(observations, assigned) {
+            search(observations, domains, assigned, found);
+        }
+        assigned.remove(&wire);
+    }
+}
+
+fn consistent(observations: &[Observation], assigned: &BTreeMap<char, char>) -> bool {
+    observations.iter().all(|(wires, candidates)| {
+        candidates.iter().any(|glyph| {
+            assigned
+                .iter()
+                .all(|(wire, segment)| glyph.contains(segment) == wires.contains(wire))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve, Solution};
+
+    #[test]
+    fn test_full_observations() {
+        let patterns = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab";
+        let Solution::Unique(wiring) = solve(&patterns.split(' ').collect::<Vec<_>>()) else {
+            panic!("expected a unique wiring");
+        };
+        assert_eq!("a>c b>f c>g d>a e>b f>d g>e", wiring.to_string());
+        assert_eq!(Some(5), wiring.digit("cdfeb"));
+        assert_eq!(None, wiring.digit("ag"));
+    }
+
+    #[test]
+    fn test_partial_observations() {
+        // Without 1, the other nine digits still pin the wiring down.
+        let patterns = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb";
+        assert!(matches!(
+            solve(&patterns.split(' ').collect::<Vec<_>>()),
+            Solution::Unique(_)
+        ));
+        // Without 7 as well, nothing tells the top segment from the bottom.
+        let patterns = "acedgfb cdfbe gcdfa fbcad cefabd cdfgeb eafb cagedb";
+        match solve(&patterns.split(' ').collect::<Vec<_>>()) {
+            Solution::Ambiguous(wirings) => assert_eq!(
+                vec!["a>c b>f c>a d>g e>b f>d g>e", "a>c b>f c>g d>a e>b f>d g>e"],
+                wirings.iter().map(|w| w.to_string()).collect::<Vec<_>>()
+            ),
+            other => panic!("expected ambiguity, got {:?}", other),
+        }
+        match solve(&["ab", "dab"]) {
+            Solution::Ambiguous(wirings) => {
+                assert!(wirings.len() > 1);
+                assert!(wirings.iter().all(|w| w.digit("ab") == Some(1)));
+            }
+            other => panic!("expected ambiguity, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_contradiction() {
+        // Two wires cannot both be 1 and share nothing with 7.
+        assert_eq!(Solution::Contradiction, solve(&["ab", "cde"]));
+        assert_eq!(Solution::Contradiction, solve(&["abcdefgh"]));
+        assert_eq!(
+            Solution::Contradiction,
+            solve(&["abcde", "abcdf", "abcdg", "abcef"])
+        );
+    }
+}
@@ -4,23 +4,47 @@ pub mod diagnostic;
 pub mod lanternfish;
 pub mod navigation;
 pub mod octopus;
+pub mod planner;
+pub mod population;
+pub mod snapshot;
 
 use std::{
+    collections::VecDeque,
     fs::File,
     io::{self, prelude::*, BufReader},
 };
 
+use self::snapshot::Snapshot;
+
 pub struct Submarine {
     pos: Position,
+    model: ControlModel,
     control: Box<dyn Control>,
+    index: usize,
+    history: VecDeque<(Direction, i32)>,
+    undo_limit: Option<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ControlModel {
+    V1,
+    V2,
 }
 
+#[derive(Clone, Copy)]
 struct Position {
     x: i32,
     depth: i32,
     aim: i32,
 }
 
+#[derive(Clone, Copy)]
+enum Direction {
+    Up,
+    Down,
+    Forward,
+}
+
 trait Control {
     fn forward(&self, prev: &Position, unit: i32) -> Position;
     fn up(&self, prev: &Position, unit: i32) -> Position;
@@ -30,6 +54,15 @@ trait Control {
 struct ControlV1;
 struct ControlV2;
 
+impl ControlModel {
+    fn control(&self) -> Box<dyn Control> {
+        match self {
+            ControlModel::V1 => Box::new(ControlV1),
+            ControlModel::V2 => Box::new(ControlV2),
+        }
+    }
+}
+
 impl Control for ControlV1 {
     fn forward(&self, prev: &Position, unit: i32) -> Position {
         Position {
@@ -79,24 +112,57 @@ impl Control for ControlV2 {
 
 impl Submarine {
     pub fn v1() -> Submarine {
+        Submarine::with_model(ControlModel::V1)
+    }
+
+    pub fn v2() -> Submarine {
+        Submarine::with_model(ControlModel::V2)
+    }
+
+    pub fn with_model(model: ControlModel) -> Submarine {
         Submarine {
             pos: Position {
                 x: 0,
                 depth: 0,
                 aim: 0,
             },
-            control: Box::new(ControlV1),
+            model,
+            control: model.control(),
+            index: 0,
+            history: VecDeque::new(),
+            undo_limit: None,
         }
     }
 
-    pub fn v2() -> Submarine {
+    /// Remember only the last `limit` commands for `undo`.
+    pub fn with_undo_limit(self, limit: usize) -> Submarine {
+        Submarine {
+            undo_limit: Some(limit),
+            ..self
+        }
+    }
+
+    /// Rebuild a submarine from a checkpoint. Commands run before the
+    /// checkpoint cannot be undone.
+    pub fn restore(snapshot: &Snapshot) -> Submarine {
         Submarine {
             pos: Position {
-                x: 0,
-                depth: 0,
-                aim: 0,
+                x: snapshot.x,
+                depth: snapshot.depth,
+                aim: snapshot.aim,
             },
-            control: Box::new(ControlV2),
+            index: snapshot.index,
+            ..Submarine::with_model(snapshot.model)
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            model: self.model,
+            x: self.pos.x,
+            depth: self.pos.depth,
+            aim: self.pos.aim,
+            index: self.index,
         }
     }
 
@@ -109,20 +175,72 @@ impl Submarine {
     pub fn run(&mut self, input: &[String]) {
         for inst in input {
             let mut split = inst.split(' ');
-            let dir = split.next().unwrap();
+            let dir = match split.next().unwrap() {
+                "up" => Direction::Up,
+                "down" => Direction::Down,
+                "forward" => Direction::Forward,
+                dir => panic!("unknown instruction {}", dir),
+            };
             let unit = split.next().unwrap().parse::<i32>().unwrap();
-            match dir {
-                "up" => self.pos = self.control.up(&self.pos, unit),
-                "down" => self.pos = self.control.down(&self.pos, unit),
-                "forward" => self.pos = self.control.forward(&self.pos, unit),
-                _ => panic!("unknown instruction {}", dir),
+            self.apply(dir, unit);
+            if self.undo_limit == Some(self.history.len()) {
+                self.history.pop_front();
+            }
+            if self.undo_limit != Some(0) {
+                self.history.push_back((dir, unit));
             }
+            self.index += 1;
         }
     }
 
+    fn apply(&mut self, dir: Direction, unit: i32) {
+        self.pos = match dir {
+            Direction::Up => self.control.up(&self.pos, unit),
+            Direction::Down => self.control.down(&self.pos, unit),
+            Direction::Forward => self.control.forward(&self.pos, unit),
+        };
+    }
+
+    /// Continue an instruction file from the first command not yet run.
+    pub fn resume(&mut self, input: &[String]) -> io::Result<()> {
+        if self.index > input.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "resuming at command {} of a {}-command file",
+                    self.index,
+                    input.len()
+                ),
+            ));
+        }
+        self.run(&input[self.index..]);
+        Ok(())
+    }
+
+    /// Roll back the last `n` commands and return how many were undone.
+    /// Every command is reversed by running it again with the opposite
+    /// unit.
+    pub fn undo(&mut self, n: usize) -> usize {
+        let n = n.min(self.history.len());
+        for _ in 0..n {
+            let (dir, unit) = self.history.pop_back().unwrap();
+            self.apply(dir, -unit);
+            self.index -= 1;
+        }
+        n
+    }
+
+    pub fn command_index(&self) -> usize {
+        self.index
+    }
+
     pub fn report(&self) -> i32 {
         self.pos.x * self.pos.depth
     }
+
+    pub fn position(&self) -> (i32, i32) {
+        (self.pos.x, self.pos.depth)
+    }
 }
 
 #[cfg(test)]
@@ -1,11 +1,80 @@
+pub mod caller;
+pub mod generator;
+pub mod validation;
+
 use std::{
+    collections::HashMap,
     fs::File,
     io::{self, BufRead, BufReader, Read},
 };
 
+use crate::invalid;
+
 pub struct BingoSystem {
     seq: Vec<i32>,
-    boards: Vec<FiveByFiveBoard>,
+    boards: Vec<Board>,
+    /// Where each number sits, as `(board, cell)` sorted by board.
+    index: HashMap<i32, Vec<(usize, usize)>>,
+}
+
+/// A way to complete a card. Each pattern stands for one or more lines of
+/// cells, and a board wins once every cell of any line is marked.
+#[derive(Clone, PartialEq, Debug)]
+pub enum WinPattern {
+    Rows,
+    Columns,
+    /// Both diagonals, on square boards only.
+    Diagonals,
+    FourCorners,
+    FullCard,
+    /// Custom cells as `(row, col)`, ignored on boards too small for it.
+    Mask(Vec<(usize, usize)>),
+}
+
+/// A board completing a line during the game.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BingoWin {
+    pub board: usize,
+    /// Index of the winning draw in the sequence.
+    pub draw: usize,
+    pub number: i32,
+    /// Cells of the completed line as `(row, col)`.
+    pub line: Vec<(usize, usize)>,
+    pub score: i32,
+}
+
+pub const DEFAULT_PATTERNS: [WinPattern; 2] = [WinPattern::Rows, WinPattern::Columns];
+
+impl WinPattern {
+    fn lines(&self, rows: usize, cols: usize) -> Vec<Vec<usize>> {
+        match self {
+            WinPattern::Rows => (0..rows)
+                .map(|r| (0..cols).map(|col| r * cols + col).collect())
+                .collect(),
+            WinPattern::Columns => (0..cols)
+                .map(|col| (0..rows).map(|r| r * cols + col).collect())
+                .collect(),
+            WinPattern::Diagonals if rows == cols => vec![
+                (0..rows).map(|i| i * cols + i).collect(),
+                (0..rows).map(|i| i * cols + cols - 1 - i).collect(),
+            ],
+            WinPattern::Diagonals => vec![],
+            WinPattern::FourCorners => {
+                let mut corners = vec![0, cols - 1, (rows - 1) * cols, rows * cols - 1];
+                corners.sort_unstable();
+                corners.dedup();
+                vec![corners]
+            }
+            WinPattern::FullCard => vec![(0..rows * cols).collect()],
+            WinPattern::Mask(cells) => {
+                if cells.is_empty() || cells.iter().any(|&(r, col)| r >= rows || col >= cols) {
+                    vec![]
+                } else {
+                    vec![cells.iter().map(|&(r, col)| r * cols + col).collect()]
+                }
+            }
+        }
+    }
 }
 
 impl BingoSystem {
@@ -16,236 +85,330 @@ impl BingoSystem {
         BingoSystem::from_str(&buf)
     }
 
+    /// Read the draw sequence followed by boards separated by blank lines.
+    /// Each board takes its size from its own rows.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(input: &str) -> io::Result<BingoSystem> {
-        let mut reader = BufReader::new(input.as_bytes());
-        let mut buf = String::new();
-        reader.read_line(&mut buf)?;
-        let seq = buf
-            .trim_end()
-            .split(',')
-            .map(|s| s.parse().unwrap())
-            .collect::<Vec<i32>>();
+        let reader = BufReader::new(input.as_bytes());
+        let mut lines = reader.lines();
+        let seq = match lines.next() {
+            Some(line) => line?
+                .trim()
+                .split(',')
+                .filter(|s| !s.trim().is_empty())
+                .map(parse_number)
+                .collect::<io::Result<Vec<i32>>>()?,
+            None => vec![],
+        };
 
         // Read boards
         let mut boards = vec![];
-        let mut numbers = vec![];
-        loop {
-            numbers.clear();
-            buf.clear();
-            if reader.read_line(&mut buf)? == 0 {
-                break;
-            }
-
-            for _ in 0..5 {
-                buf.clear();
-                reader.read_line(&mut buf)?;
-                numbers.extend(
-                    buf.trim()
-                        .split(' ')
-                        .filter(|s| !s.is_empty())
-                        .map(|s| s.parse::<i32>().unwrap()),
-                )
+        let mut rows: Vec<Vec<i32>> = vec![];
+        for line in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                if !rows.is_empty() {
+                    boards.push(Board::from_rows(&rows, &DEFAULT_PATTERNS)?);
+                    rows.clear();
+                }
+                continue;
             }
-            boards.push(FiveByFiveBoard::with_numbers(&numbers));
+            rows.push(
+                line.split_whitespace()
+                    .map(parse_number)
+                    .collect::<io::Result<_>>()?,
+            );
+        }
+        if !rows.is_empty() {
+            boards.push(Board::from_rows(&rows, &DEFAULT_PATTERNS)?);
         }
 
-        Ok(BingoSystem { seq, boards })
+        Ok(BingoSystem::new(seq, boards))
     }
 
-    pub fn bingo_to_win(&self) -> i32 {
-        let mut boards = self.boards.clone();
-        for &number in &self.seq {
-            for board in &mut boards {
-                board.check_and_mark(number);
-                if board.is_win() {
-                    return board.score(number);
-                }
+    fn new(seq: Vec<i32>, boards: Vec<Board>) -> BingoSystem {
+        let mut index: HashMap<i32, Vec<(usize, usize)>> = HashMap::new();
+        for (b, board) in boards.iter().enumerate() {
+            for (cell, &number) in board.numbers.iter().enumerate() {
+                index.entry(number).or_default().push((b, cell));
             }
         }
-        unreachable!("must have a winner")
+        BingoSystem { seq, boards, index }
+    }
+
+    /// Replace the winning patterns of every board, rows and columns by
+    /// default.
+    pub fn with_patterns(mut self, patterns: &[WinPattern]) -> BingoSystem {
+        for board in &mut self.boards {
+            board.set_patterns(patterns);
+        }
+        self
+    }
+
+    /// Play the whole sequence and list every board in the order it won.
+    /// Boards stop playing once they have won.
+    pub fn play(&self) -> Vec<BingoWin> {
+        self.play_seq(&self.seq)
     }
 
-    pub fn bingo_to_lose(&self) -> i32 {
-        let mut boards = self.boards.clone();
-        let mut last_win_board = FiveByFiveBoard::new();
-        let mut last_win_num = 0;
-        for &number in &self.seq {
-            boards = boards
-                .iter_mut()
-                .map(|board| {
-                    board.check_and_mark(number);
-                    board
-                })
-                .filter(|&&mut board| {
-                    if board.is_win() {
-                        last_win_board = board;
-                        last_win_num = number;
-                        false
-                    } else {
-                        true
-                    }
-                })
-                .map(|b| *b)
-                .collect();
+    fn play_seq(&self, seq: &[i32]) -> Vec<BingoWin> {
+        let mut caller = self.caller();
+        let mut wins = vec![];
+        for &number in seq {
+            wins.extend(caller.draw(number));
         }
-        last_win_board.score(last_win_num)
+        wins
+    }
+
+    /// Score of the first board to win, `None` if no board ever wins.
+    pub fn bingo_to_win(&self) -> Option<i32> {
+        self.play().first().map(|win| win.score)
+    }
+
+    /// Score of the last board to win, `None` if no board ever wins.
+    pub fn bingo_to_lose(&self) -> Option<i32> {
+        self.play().last().map(|win| win.score)
     }
 }
 
-#[derive(Clone, Copy)]
-struct FiveByFiveBoard {
-    numbers: [i32; 25],
-    markers: [bool; 25],
+fn parse_number(s: &str) -> io::Result<i32> {
+    s.trim()
+        .parse()
+        .map_err(|_| invalid(format!("invalid bingo number {:?}", s)))
 }
 
-impl FiveByFiveBoard {
-    pub fn new() -> FiveByFiveBoard {
-        FiveByFiveBoard {
-            numbers: [0; 25],
-            markers: [false; 25],
-        }
+#[derive(Clone)]
+struct Board {
+    rows: usize,
+    cols: usize,
+    numbers: Vec<i32>,
+    lines: Vec<Vec<usize>>,
+    /// Indices into `lines` of the lines going through each cell.
+    cell_lines: Vec<Vec<usize>>,
+}
+
+/// Marking progress of one board during a game.
+#[derive(Clone)]
+struct Marking {
+    markers: Vec<bool>,
+    /// Marked cells on each line of the board.
+    hits: Vec<usize>,
+    unmarked: i32,
+}
+
+impl Board {
+    fn with_numbers(rows: usize, cols: usize, numbers: &[i32], patterns: &[WinPattern]) -> Board {
+        assert_eq!(rows * cols, numbers.len(), "wrong numbers length");
+        let mut board = Board {
+            rows,
+            cols,
+            numbers: numbers.to_vec(),
+            lines: vec![],
+            cell_lines: vec![],
+        };
+        board.set_patterns(patterns);
+        board
     }
 
-    pub fn with_numbers(numbers: &[i32]) -> FiveByFiveBoard {
-        FiveByFiveBoard {
-            numbers: numbers.try_into().expect("wrong numbers length"),
-            markers: [false; 25],
+    fn from_rows(rows: &[Vec<i32>], patterns: &[WinPattern]) -> io::Result<Board> {
+        let cols = rows[0].len();
+        if let Some(r) = rows.iter().position(|row| row.len() != cols) {
+            return Err(invalid(format!(
+                "board row {} has {} numbers, expected {}",
+                r + 1,
+                rows[r].len(),
+                cols
+            )));
         }
+        Ok(Board::with_numbers(
+            rows.len(),
+            cols,
+            &rows.concat(),
+            patterns,
+        ))
     }
 
-    fn is_win(&self) -> bool {
-        // Check rows first
-        for r in 0..5 {
-            let mut win = true;
-            for col in 0..5 {
-                if !self.markers[r * 5 + col] {
-                    win = false;
-                    break;
-                }
-            }
-            if win {
-                return true;
-            }
-        }
-        // Now check columns
-        for col in 0..5 {
-            let mut win = true;
-            for r in 0..5 {
-                if !self.markers[r * 5 + col] {
-                    win = false;
-                    break;
-                }
-            }
-            if win {
-                return true;
+    fn set_patterns(&mut self, patterns: &[WinPattern]) {
+        self.lines = patterns
+            .iter()
+            .flat_map(|pattern| pattern.lines(self.rows, self.cols))
+            .collect();
+        self.cell_lines = vec![vec![]; self.numbers.len()];
+        for (l, line) in self.lines.iter().enumerate() {
+            for &cell in line {
+                self.cell_lines[cell].push(l);
             }
         }
-        false
     }
 
-    fn check_and_mark(&mut self, number: i32) {
-        if let Some(index) =
-            self.numbers
-                .iter()
-                .enumerate()
-                .find_map(|(i, &val)| if val == number { Some(i) } else { None })
-        {
-            self.markers[index] = true;
+    fn marking(&self) -> Marking {
+        Marking {
+            markers: vec![false; self.numbers.len()],
+            hits: vec![0; self.lines.len()],
+            unmarked: self.numbers.iter().sum(),
         }
     }
 
-    fn score(&self, called: i32) -> i32 {
-        let mut sum = 0;
-        for r in 0..5 {
-            for col in 0..5 {
-                if !self.markers[r * 5 + col] {
-                    sum += self.numbers[r * 5 + col];
-                }
+    /// Mark a cell and return the first line it completes, if any.
+    fn mark(&self, marking: &mut Marking, cell: usize) -> Option<usize> {
+        if marking.markers[cell] {
+            return None;
+        }
+        marking.markers[cell] = true;
+        marking.unmarked -= self.numbers[cell];
+        let mut completed = None;
+        for &l in &self.cell_lines[cell] {
+            marking.hits[l] += 1;
+            if marking.hits[l] == self.lines[l].len() && completed.is_none() {
+                completed = Some(l);
             }
         }
-        sum * called
+        completed
+    }
+
+    fn coord(&self, cell: usize) -> (usize, usize) {
+        (cell / self.cols, cell % self.cols)
+    }
+}
+
+impl Marking {
+    fn score(&self, called: i32) -> i32 {
+        self.unmarked * called
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{BingoSystem, FiveByFiveBoard};
+    use super::{BingoSystem, BingoWin, Board, WinPattern, DEFAULT_PATTERNS};
+
+    fn winning(markers: [bool; 25]) -> bool {
+        let board = Board::with_numbers(5, 5, &[0; 25], &DEFAULT_PATTERNS);
+        let mut marking = board.marking();
+        let mut win = false;
+        for (cell, _) in markers.iter().enumerate().filter(|(_, &m)| m) {
+            win |= board.mark(&mut marking, cell).is_some();
+        }
+        win
+    }
 
     #[test]
     fn test_winning() {
-        let board = FiveByFiveBoard {
-            numbers: [0; 25],
-            #[rustfmt::skip]
-            markers: [
-                false, false, false, false, false,
-                 true,  true,  true,  true,  true,
-                false, false, false, false, false,
-                false, false, false, false, false,
-                false, false, false, false, false,
-            ],
-        };
-        assert!(board.is_win());
-
-        let board = FiveByFiveBoard {
-            numbers: [0; 25],
-            #[rustfmt::skip]
-            markers: [
-                false, true, false, false, false,
-                false, true, false, false, false,
-                false, true, false, false, false,
-                false, true, false, false, false,
-                false, true, false, false, false,
-            ],
-        };
-        assert!(board.is_win());
+        #[rustfmt::skip]
+        assert!(winning([
+            false, false, false, false, false,
+             true,  true,  true,  true,  true,
+            false, false, false, false, false,
+            false, false, false, false, false,
+            false, false, false, false, false,
+        ]));
+
+        #[rustfmt::skip]
+        assert!(winning([
+            false, true, false, false, false,
+            false, true, false, false, false,
+            false, true, false, false, false,
+            false, true, false, false, false,
+            false, true, false, false, false,
+        ]));
     }
 
     #[test]
     fn test_no_winning() {
-        let board = FiveByFiveBoard {
-            numbers: [0; 25],
-            #[rustfmt::skip]
-            markers: [
-                false, false, false, false, false,
-                true, false, true, true, true,
-                false, false, false, false, false,
-                false, false, false, false, false,
-                false, false, false, false, false,
-            ],
-        };
-        assert!(!board.is_win());
-
-        let board = FiveByFiveBoard {
-            numbers: [0; 25],
-            #[rustfmt::skip]
-            markers: [
-                false, true, false, false, false,
-                false, true, false, false, false,
-                false, false, false, false, false,
-                false, true, false, false, false,
-                false, true, false, false, false,
-            ],
-        };
-        assert!(!board.is_win());
+        #[rustfmt::skip]
+        assert!(!winning([
+            false, false, false, false, false,
+            true, false, true, true, true,
+            false, false, false, false, false,
+            false, false, false, false, false,
+            false, false, false, false, false,
+        ]));
+
+        #[rustfmt::skip]
+        assert!(!winning([
+            false, true, false, false, false,
+            false, true, false, false, false,
+            false, false, false, false, false,
+            false, true, false, false, false,
+            false, true, false, false, false,
+        ]));
     }
 
     #[test]
     fn test_mark_some_numbers() {
-        let mut board = FiveByFiveBoard {
-            #[rustfmt::skip]
-            numbers: [
-                22, 13, 17, 11,  0,
-                 8,  2, 23,  4, 24,
-                21,  9, 14, 16,  7,
-                 6, 10,  3, 18,  5,
-                 1, 12, 20, 15, 19,
-            ],
-            markers: [false; 25],
-        };
-        board.check_and_mark(14);
-        board.check_and_mark(19);
-        assert_eq!(true, board.markers[12]);
-        assert_eq!(true, board.markers[24]);
+        #[rustfmt::skip]
+        let board = Board::with_numbers(5, 5, &[
+            22, 13, 17, 11,  0,
+             8,  2, 23,  4, 24,
+            21,  9, 14, 16,  7,
+             6, 10,  3, 18,  5,
+             1, 12, 20, 15, 19,
+        ], &DEFAULT_PATTERNS);
+        let bingo_system = BingoSystem::new(vec![14, 19], vec![board.clone(), board]);
+        assert_eq!(vec![(0, 12), (1, 12)], bingo_system.index[&14]);
+        assert_eq!(vec![(0, 24), (1, 24)], bingo_system.index[&19]);
+
+        let board = &bingo_system.boards[0];
+        let mut marking = board.marking();
+        assert_eq!(None, board.mark(&mut marking, 12));
+        assert_eq!(None, board.mark(&mut marking, 12));
+        assert!(marking.markers[12]);
+        assert_eq!(vec![0, 0, 1, 0, 0, 0, 0, 1, 0, 0], marking.hits);
+        assert_eq!(300 - 14, marking.unmarked);
+    }
+
+    #[test]
+    fn test_win_patterns() {
+        let lines = |pattern: WinPattern, rows, cols| pattern.lines(rows, cols);
+        assert_eq!(
+            vec![vec![0, 4, 8], vec![2, 4, 6]],
+            lines(WinPattern::Diagonals, 3, 3)
+        );
+        assert!(lines(WinPattern::Diagonals, 3, 4).is_empty());
+        assert_eq!(
+            vec![vec![0, 3, 8, 11]],
+            lines(WinPattern::FourCorners, 3, 4)
+        );
+        assert_eq!(
+            vec![(0..6).collect::<Vec<_>>()],
+            lines(WinPattern::FullCard, 2, 3)
+        );
+        assert_eq!(
+            vec![vec![1, 5]],
+            lines(WinPattern::Mask(vec![(0, 1), (1, 2)]), 3, 3)
+        );
+        assert!(lines(WinPattern::Mask(vec![(3, 0)]), 3, 3).is_empty());
+    }
+
+    #[test]
+    fn test_rectangular_boards() {
+        let bingo_system = BingoSystem::from_str(
+            "1,2,3,4,5,6,7,8,9
+
+            1 5 9
+            7 3 6
+
+            2 4
+            8 9
+            6 1",
+        )
+        .expect("fail to create a bingo system");
+        assert_eq!(
+            (2, 3),
+            (bingo_system.boards[0].rows, bingo_system.boards[0].cols)
+        );
+        assert_eq!(
+            (3, 2),
+            (bingo_system.boards[1].rows, bingo_system.boards[1].cols)
+        );
+        // 2 4 completes the first row of the second board.
+        assert_eq!(Some((8 + 9 + 6) * 4), bingo_system.bingo_to_win());
+
+        let bingo_system = bingo_system.with_patterns(&[WinPattern::FourCorners]);
+        // Corners 2 4 6 1 of the second board are done before 1 9 7 6.
+        assert_eq!(Some((8 + 9) * 6), bingo_system.bingo_to_win());
+
+        assert!(BingoSystem::from_str("1,2\n\n1 2\n3\n").is_err());
+        assert!(BingoSystem::from_str("1,x\n\n1 2\n").is_err());
     }
 
     #[test]
@@ -272,7 +435,25 @@ mod tests {
              2  0 12  3  7",
         )
         .expect("fail to create a bingo system");
-        assert_eq!(4512, bingo_system.bingo_to_win());
-        assert_eq!(1924, bingo_system.bingo_to_lose());
+        assert_eq!(Some(4512), bingo_system.bingo_to_win());
+        assert_eq!(Some(1924), bingo_system.bingo_to_lose());
+
+        let wins = bingo_system.play();
+        assert_eq!(
+            vec![(2, 11, 24), (0, 13, 16), (1, 14, 13)],
+            wins.iter()
+                .map(|win| (win.board, win.draw, win.number))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            BingoWin {
+                board: 2,
+                draw: 11,
+                number: 24,
+                line: vec![(0, 0), (0, 1), (0, 2), (0, 3), (0, 4)],
+                score: 4512,
+            },
+            wins[0]
+        );
     }
 }
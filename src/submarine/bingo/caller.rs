@@ -0,0 +1,245 @@
+use std::io::{self, BufRead, Write};
+
+use super::{BingoSystem, BingoWin, Board, Marking};
+
+/// A live game over the boards of a `BingoSystem`, fed one number at a
+/// time instead of replaying its draw sequence.
+pub struct BingoCaller<'a> {
+    system: &'a BingoSystem,
+    markings: Vec<Marking>,
+    won: Vec<bool>,
+    draws: usize,
+}
+
+/// A board one number away from completing a line.
+#[derive(PartialEq, Debug)]
+pub struct NearMiss {
+    pub board: usize,
+    /// Numbers that would each complete a line, in ascending order.
+    pub needs: Vec<i32>,
+}
+
+impl BingoSystem {
+    pub fn caller(&self) -> BingoCaller<'_> {
+        BingoCaller {
+            system: self,
+            markings: self.boards.iter().map(Board::marking).collect(),
+            won: vec![false; self.boards.len()],
+            draws: 0,
+        }
+    }
+}
+
+impl BingoCaller<'_> {
+    /// Call a number and return the boards it completes, in board order.
+    pub fn draw(&mut self, number: i32) -> Vec<BingoWin> {
+        let draw = self.draws;
+        self.draws += 1;
+        let cells = match self.system.index.get(&number) {
+            Some(cells) => cells,
+            None => return vec![],
+        };
+        // Mark every occurrence first so scores see the whole draw.
+        let mut completed: Vec<(usize, usize)> = vec![];
+        for &(b, cell) in cells {
+            if self.won[b] {
+                continue;
+            }
+            if let Some(line) = self.system.boards[b].mark(&mut self.markings[b], cell) {
+                if completed.last().map(|&(last, _)| last) != Some(b) {
+                    completed.push((b, line));
+                }
+            }
+        }
+        completed
+            .into_iter()
+            .map(|(b, line)| {
+                self.won[b] = true;
+                let board = &self.system.boards[b];
+                BingoWin {
+                    board: b,
+                    draw,
+                    number,
+                    line: board.lines[line]
+                        .iter()
+                        .map(|&cell| board.coord(cell))
+                        .collect(),
+                    score: self.markings[b].score(number),
+                }
+            })
+            .collect()
+    }
+
+    /// Boards still playing that one more number would complete.
+    pub fn near_misses(&self) -> Vec<NearMiss> {
+        let mut misses = vec![];
+        for (b, board) in self.system.boards.iter().enumerate() {
+            if self.won[b] {
+                continue;
+            }
+            let marking = &self.markings[b];
+            let mut needs: Vec<i32> = board
+                .lines
+                .iter()
+                .zip(&marking.hits)
+                .filter(|(line, &hits)| hits + 1 == line.len())
+                .filter_map(|(line, _)| line.iter().find(|&&cell| !marking.markers[cell]))
+                .map(|&cell| board.numbers[cell])
+                .collect();
+            if !needs.is_empty() {
+                needs.sort_unstable();
+                needs.dedup();
+                misses.push(NearMiss { board: b, needs });
+            }
+        }
+        misses
+    }
+
+    /// Draw a board with marked numbers in brackets.
+    pub fn render(&self, board: usize) -> String {
+        let marking = &self.markings[board];
+        let board = &self.system.boards[board];
+        let width = board
+            .numbers
+            .iter()
+            .map(|n| n.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let mut out = String::new();
+        for r in 0..board.rows {
+            let mut row = String::new();
+            for col in 0..board.cols {
+                let cell = r * board.cols + col;
+                let number = board.numbers[cell];
+                if marking.markers[cell] {
+                    row.push_str(&format!("[{:>width$}]", number, width = width));
+                } else {
+                    row.push_str(&format!(" {:>width$} ", number, width = width));
+                }
+            }
+            out.push_str(row.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Read draws from `input`, one or more comma separated numbers per
+    /// line, and report wins and near misses after each. `show N` renders
+    /// board N and `quit` stops the game.
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line == "quit" {
+                break;
+            }
+            if let Some(board) = line.strip_prefix("show ") {
+                match board.trim().parse::<usize>() {
+                    Ok(board) if board < self.system.boards.len() => {
+                        write!(output, "{}", self.render(board))?
+                    }
+                    _ => writeln!(output, "no board {}", board.trim())?,
+                }
+                continue;
+            }
+            for number in line.split(',').filter(|s| !s.trim().is_empty()) {
+                let number = match number.trim().parse::<i32>() {
+                    Ok(number) => number,
+                    Err(_) => {
+                        writeln!(output, "not a number: {}", number.trim())?;
+                        continue;
+                    }
+                };
+                for win in self.draw(number) {
+                    writeln!(
+                        output,
+                        "{}: board {} wins with score {}",
+                        number, win.board, win.score
+                    )?;
+                }
+            }
+            for miss in self.near_misses() {
+                let needs: Vec<_> = miss.needs.iter().map(|n| n.to_string()).collect();
+                writeln!(output, "board {} needs {}", miss.board, needs.join(" or "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NearMiss;
+    use crate::submarine::bingo::BingoSystem;
+
+    const GAME: &str = "
+    1 2 3
+    4 5 6
+    7 8 9
+
+    9 8
+    7 6";
+
+    #[test]
+    fn test_draw_and_near_misses() {
+        let bingo_system = BingoSystem::from_str(GAME).unwrap();
+        let mut caller = bingo_system.caller();
+        assert!(caller.draw(1).is_empty());
+        assert!(caller.near_misses().is_empty());
+        assert!(caller.draw(2).is_empty());
+        assert_eq!(
+            vec![NearMiss {
+                board: 0,
+                needs: vec![3]
+            }],
+            caller.near_misses()
+        );
+        caller.draw(9);
+        assert_eq!(
+            vec![
+                NearMiss {
+                    board: 0,
+                    needs: vec![3]
+                },
+                NearMiss {
+                    board: 1,
+                    needs: vec![7, 8]
+                }
+            ],
+            caller.near_misses()
+        );
+        let wins = caller.draw(8);
+        assert_eq!(1, wins.len());
+        assert_eq!(
+            (1, 3, 8 * (7 + 6)),
+            (wins[0].board, wins[0].draw, wins[0].score)
+        );
+        assert_eq!("[9][8]\n 7  6\n", caller.render(1));
+        assert_eq!("[1][2] 3\n 4  5  6\n 7 [8][9]\n", caller.render(0));
+    }
+
+    #[test]
+    fn test_run() {
+        let bingo_system = BingoSystem::from_str(GAME).unwrap();
+        let mut output = vec![];
+        bingo_system
+            .caller()
+            .run(
+                "1,2\nx\n3\nshow 0\nshow 5\nquit\n9\n".as_bytes(),
+                &mut output,
+            )
+            .unwrap();
+        assert_eq!(
+            "board 0 needs 3
+not a number: x
+board 0 needs 3
+3: board 0 wins with score 117
+[1][2][3]
+ 4  5  6
+ 7  8  9
+no board 5
+",
+            String::from_utf8(output).unwrap()
+        );
+    }
+}
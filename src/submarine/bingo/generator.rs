@@ -0,0 +1,185 @@
+use std::{fmt::Write, io};
+
+use super::BingoSystem;
+use crate::{invalid, rng::Rng};
+
+/// Seeded source of bingo games, written in the format
+/// `BingoSystem::from_str` reads.
+pub struct BingoGenerator {
+    rng: Rng,
+    rows: usize,
+    cols: usize,
+    numbers: i32,
+}
+
+/// How a board fares over many random draw orders.
+#[derive(PartialEq, Debug)]
+pub struct BoardOdds {
+    /// Share of games the board wins first, split evenly between boards
+    /// completing a line on the same draw.
+    pub win_probability: f64,
+    /// Mean 1-based draw on which the board completes a line, over the
+    /// games where it does.
+    pub expected_win_turn: Option<f64>,
+}
+
+impl BingoGenerator {
+    /// Five by five boards drawing from the numbers `0..100`.
+    pub fn new(seed: u64) -> BingoGenerator {
+        BingoGenerator {
+            rng: Rng::new(seed),
+            rows: 5,
+            cols: 5,
+            numbers: 100,
+        }
+    }
+
+    pub fn with_board_size(self, rows: usize, cols: usize) -> BingoGenerator {
+        BingoGenerator { rows, cols, ..self }
+    }
+
+    /// Draw from the numbers `0..numbers`.
+    pub fn with_numbers(self, numbers: i32) -> BingoGenerator {
+        BingoGenerator { numbers, ..self }
+    }
+
+    /// A shuffled draw of every number followed by `boards` boards, each
+    /// holding distinct numbers.
+    pub fn generate(&mut self, boards: usize) -> io::Result<String> {
+        let cells = self
+            .rows
+            .checked_mul(self.cols)
+            .filter(|&cells| cells > 0)
+            .ok_or_else(|| invalid(format!("bad board size {}x{}", self.rows, self.cols)))?;
+        if usize::try_from(self.numbers).map_or(true, |numbers| numbers < cells) {
+            return Err(invalid(format!(
+                "{} numbers cannot fill a board of {} cells",
+                self.numbers, cells
+            )));
+        }
+        let mut pool: Vec<i32> = (0..self.numbers).collect();
+        self.rng.shuffle(&mut pool);
+        let mut out = pool
+            .iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        out.push('\n');
+
+        let width = (self.numbers - 1).to_string().len();
+        for _ in 0..boards {
+            self.rng.shuffle(&mut pool);
+            out.push('\n');
+            for row in pool[..cells].chunks(self.cols) {
+                let row: Vec<_> = row
+                    .iter()
+                    .map(|n| format!("{:>width$}", n, width = width))
+                    .collect();
+                writeln!(out, "{}", row.join(" ")).unwrap();
+            }
+        }
+        Ok(out)
+    }
+}
+
+impl BingoSystem {
+    /// Replay the game over `trials` random orders of the draw sequence.
+    pub fn analyze(&self, trials: usize, seed: u64) -> Vec<BoardOdds> {
+        let mut rng = Rng::new(seed);
+        let mut seq = self.seq.clone();
+        let mut wins = vec![0.0; self.boards.len()];
+        let mut turns = vec![(0, 0); self.boards.len()];
+        for _ in 0..trials {
+            rng.shuffle(&mut seq);
+            let timeline = self.play_seq(&seq);
+            if let Some(first) = timeline.first() {
+                let winners: Vec<_> = timeline
+                    .iter()
+                    .take_while(|win| win.draw == first.draw)
+                    .collect();
+                for win in &winners {
+                    wins[win.board] += 1.0 / winners.len() as f64;
+                }
+            }
+            for win in &timeline {
+                let (total, games) = &mut turns[win.board];
+                *total += win.draw + 1;
+                *games += 1;
+            }
+        }
+        wins.iter()
+            .zip(turns)
+            .map(|(&wins, (total, games))| BoardOdds {
+                win_probability: wins / trials.max(1) as f64,
+                expected_win_turn: if games > 0 {
+                    Some(total as f64 / games as f64)
+                } else {
+                    None
+                },
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::BingoGenerator;
+    use crate::submarine::bingo::BingoSystem;
+
+    #[test]
+    fn test_generate() {
+        let input = BingoGenerator::new(7).generate(3).unwrap();
+        assert_eq!(input, BingoGenerator::new(7).generate(3).unwrap());
+        assert_ne!(input, BingoGenerator::new(8).generate(3).unwrap());
+
+        let bingo_system = BingoSystem::from_str(&input).expect("unreadable game");
+        assert_eq!(100, bingo_system.seq.len());
+        assert_eq!(100, bingo_system.seq.iter().collect::<HashSet<_>>().len());
+        assert_eq!(3, bingo_system.boards.len());
+        for board in &bingo_system.boards {
+            assert_eq!((5, 5), (board.rows, board.cols));
+            assert_eq!(25, board.numbers.iter().collect::<HashSet<_>>().len());
+        }
+        assert_eq!(3, bingo_system.play().len());
+
+        let input = BingoGenerator::new(1)
+            .with_board_size(2, 3)
+            .with_numbers(6)
+            .generate(1)
+            .unwrap();
+        let bingo_system = BingoSystem::from_str(&input).expect("unreadable game");
+        assert_eq!(
+            (2, 3),
+            (bingo_system.boards[0].rows, bingo_system.boards[0].cols)
+        );
+    }
+
+    #[test]
+    fn test_bad_settings() {
+        let generator = || BingoGenerator::new(1);
+        assert!(generator().with_numbers(-5).generate(1).is_err());
+        assert!(generator().with_numbers(24).generate(1).is_err());
+        assert!(generator().with_board_size(0, 5).generate(1).is_err());
+        assert!(generator()
+            .with_board_size(usize::MAX, 2)
+            .generate(1)
+            .is_err());
+    }
+
+    #[test]
+    fn test_analyze() {
+        // The second board is a copy of the first, the third shares no
+        // number with the draw and never wins.
+        let bingo_system =
+            BingoSystem::from_str("1,2,3,4\n\n1 2\n3 4\n\n1 2\n3 4\n\n5 6\n7 8\n").unwrap();
+        let odds = bingo_system.analyze(200, 42);
+        assert_eq!(odds[0], odds[1]);
+        assert_eq!(0.5, odds[0].win_probability);
+        assert_eq!(0.0, odds[2].win_probability);
+        assert_eq!(None, odds[2].expected_win_turn);
+        let turn = odds[0].expected_win_turn.unwrap();
+        assert!((2.0..=3.0).contains(&turn));
+    }
+}
@@ -0,0 +1,261 @@
+use std::{collections::HashSet, fmt};
+
+use super::{BingoSystem, WinPattern};
+
+/// A problem found in a bingo game description. Input lines and board
+/// rows count from 1, like the parser's errors; boards and `(row, col)`
+/// cells count from 0, like `BingoWin`.
+#[derive(PartialEq, Debug)]
+pub enum BingoFinding {
+    InvalidNumber {
+        line: usize,
+        text: String,
+    },
+    RaggedRow {
+        board: usize,
+        row: usize,
+        len: usize,
+        expected: usize,
+    },
+    DuplicateNumber {
+        board: usize,
+        number: i32,
+        cells: Vec<(usize, usize)>,
+    },
+    NeverDrawn {
+        board: usize,
+        number: i32,
+    },
+    CannotWin {
+        board: usize,
+    },
+}
+
+impl BingoSystem {
+    /// Check a game in the `from_str` format without building it. Boards
+    /// with ragged rows are not checked for winning lines.
+    pub fn validate(input: &str, patterns: &[WinPattern]) -> Vec<BingoFinding> {
+        let mut findings = vec![];
+        let mut lines = input.lines().enumerate();
+        let mut drawn = HashSet::new();
+        if let Some((n, line)) = lines.next() {
+            for text in line.trim().split(',').filter(|s| !s.trim().is_empty()) {
+                match text.trim().parse::<i32>() {
+                    Ok(number) => {
+                        drawn.insert(number);
+                    }
+                    Err(_) => findings.push(BingoFinding::InvalidNumber {
+                        line: n + 1,
+                        text: text.trim().to_string(),
+                    }),
+                }
+            }
+        }
+
+        let mut boards: Vec<Vec<Vec<i32>>> = vec![];
+        let mut in_board = false;
+        for (n, line) in lines {
+            if line.trim().is_empty() {
+                in_board = false;
+                continue;
+            }
+            if !in_board {
+                boards.push(vec![]);
+                in_board = true;
+            }
+            let mut row = vec![];
+            for text in line.split_whitespace() {
+                match text.parse::<i32>() {
+                    Ok(number) => row.push(number),
+                    Err(_) => findings.push(BingoFinding::InvalidNumber {
+                        line: n + 1,
+                        text: text.to_string(),
+                    }),
+                }
+            }
+            boards.last_mut().unwrap().push(row);
+        }
+
+        for (b, rows) in boards.iter().enumerate() {
+            findings.extend(check_board(b, rows, &drawn, patterns));
+        }
+        findings
+    }
+}
+
+fn check_board(
+    board: usize,
+    rows: &[Vec<i32>],
+    drawn: &HashSet<i32>,
+    patterns: &[WinPattern],
+) -> Vec<BingoFinding> {
+    let mut findings = vec![];
+    let expected = rows[0].len();
+    for (r, row) in rows.iter().enumerate() {
+        if row.len() != expected {
+            findings.push(BingoFinding::RaggedRow {
+                board,
+                row: r + 1,
+                len: row.len(),
+                expected,
+            });
+        }
+    }
+
+    let mut seen: Vec<(i32, Vec<(usize, usize)>)> = vec![];
+    for (r, row) in rows.iter().enumerate() {
+        for (col, &number) in row.iter().enumerate() {
+            match seen.iter_mut().find(|(n, _)| *n == number) {
+                Some((_, cells)) => cells.push((r, col)),
+                None => seen.push((number, vec![(r, col)])),
+            }
+        }
+    }
+    for (number, cells) in &seen {
+        if cells.len() > 1 {
+            findings.push(BingoFinding::DuplicateNumber {
+                board,
+                number: *number,
+                cells: cells.clone(),
+            });
+        }
+    }
+    for (number, _) in &seen {
+        if !drawn.contains(number) {
+            findings.push(BingoFinding::NeverDrawn {
+                board,
+                number: *number,
+            });
+        }
+    }
+
+    let ragged = rows.iter().any(|row| row.len() != expected);
+    if !ragged && expected > 0 {
+        let numbers = rows.concat();
+        let can_win = patterns
+            .iter()
+            .flat_map(|pattern| pattern.lines(rows.len(), expected))
+            .any(|line| line.iter().all(|&cell| drawn.contains(&numbers[cell])));
+        if !can_win {
+            findings.push(BingoFinding::CannotWin { board });
+        }
+    }
+    findings
+}
+
+impl fmt::Display for BingoFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BingoFinding::InvalidNumber { line, text } => {
+                write!(f, "line {}: {:?} is not a number", line, text)
+            }
+            BingoFinding::RaggedRow {
+                board,
+                row,
+                len,
+                expected,
+            } => write!(
+                f,
+                "board {}: row {} has {} numbers, expected {}",
+                board, row, len, expected
+            ),
+            BingoFinding::DuplicateNumber {
+                board,
+                number,
+                cells,
+            } => write!(f, "board {}: {} appears at {:?}", board, number, cells),
+            BingoFinding::NeverDrawn { board, number } => {
+                write!(f, "board {}: {} is never drawn", board, number)
+            }
+            BingoFinding::CannotWin { board } => write!(f, "board {}: can never win", board),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BingoFinding;
+    use crate::submarine::bingo::{BingoSystem, WinPattern, DEFAULT_PATTERNS};
+
+    #[test]
+    fn test_valid_game() {
+        let input = "1,2,3,4\n\n1 2\n3 4\n";
+        assert!(BingoSystem::validate(input, &DEFAULT_PATTERNS).is_empty());
+    }
+
+    #[test]
+    fn test_findings() {
+        let input = "1,2,x,4
+
+        1 2
+        2 9
+
+        1 2 3
+        4
+
+        7 8
+        9 5";
+        assert_eq!(
+            vec![
+                BingoFinding::InvalidNumber {
+                    line: 1,
+                    text: "x".to_string()
+                },
+                BingoFinding::DuplicateNumber {
+                    board: 0,
+                    number: 2,
+                    cells: vec![(0, 1), (1, 0)]
+                },
+                BingoFinding::NeverDrawn {
+                    board: 0,
+                    number: 9
+                },
+                BingoFinding::RaggedRow {
+                    board: 1,
+                    row: 2,
+                    len: 1,
+                    expected: 3
+                },
+                BingoFinding::NeverDrawn {
+                    board: 1,
+                    number: 3
+                },
+                BingoFinding::NeverDrawn {
+                    board: 2,
+                    number: 7
+                },
+                BingoFinding::NeverDrawn {
+                    board: 2,
+                    number: 8
+                },
+                BingoFinding::NeverDrawn {
+                    board: 2,
+                    number: 9
+                },
+                BingoFinding::NeverDrawn {
+                    board: 2,
+                    number: 5
+                },
+                BingoFinding::CannotWin { board: 2 },
+            ],
+            BingoSystem::validate(input, &DEFAULT_PATTERNS)
+        );
+        assert_eq!(
+            "board 1: row 2 has 1 numbers, expected 3",
+            BingoSystem::validate(input, &DEFAULT_PATTERNS)[3].to_string()
+        );
+    }
+
+    #[test]
+    fn test_cannot_win_with_patterns() {
+        let input = "1,4\n\n1 2\n3 4\n";
+        assert!(BingoSystem::validate(input, &DEFAULT_PATTERNS)
+            .contains(&BingoFinding::CannotWin { board: 0 }));
+        assert!(!BingoSystem::validate(input, &[WinPattern::Diagonals])
+            .contains(&BingoFinding::CannotWin { board: 0 }));
+
+        let bingo_system = BingoSystem::from_str(input).unwrap();
+        assert_eq!(None, bingo_system.bingo_to_win());
+        assert_eq!(None, bingo_system.bingo_to_lose());
+    }
+}
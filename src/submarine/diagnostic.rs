@@ -1,165 +1,552 @@
+pub mod monitor;
+
 use std::cmp::Ordering;
+use std::fmt;
 use std::fs::File;
 use std::io::{self, prelude::*, BufReader};
 
+use num_bigint::BigUint;
+
+use crate::invalid;
+
+/// Readings are kept both packed into integers and transposed into one
+/// bitset per column, so counting a column is a popcount over the
+/// candidate set.
 pub struct DiagnosticModule {
-    reading: Vec<Vec<u8>>,
+    reading: Vec<u128>,
+    width: usize,
+    columns: Vec<Vec<u64>>,
 }
 
 #[derive(PartialEq, Debug)]
 pub struct DiagnosticReport {
-    pub power_consumption: i32,
-    pub life_support_rating: i32,
+    /// Products of two ratings, which can outgrow a `u128`.
+    pub power_consumption: BigUint,
+    pub life_support_rating: BigUint,
+    pub gamma: Rating,
+    pub epsilon: Rating,
+    pub oxygen_generator: Rating,
+    pub co2_scrubber: Rating,
+    pub column_counts: Vec<ColumnCount>,
+    /// Candidates left after filtering on each column, for the oxygen
+    /// generator and CO2 scrubber ratings.
+    pub oxygen_trace: Vec<usize>,
+    pub co2_trace: Vec<usize>,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Rating {
+    pub decimal: u128,
+    pub binary: String,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct ColumnCount {
+    pub ones: usize,
+    pub zeros: usize,
 }
 
+/// What to do when a bit criteria cannot pick a bit for a column.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum TiePolicy {
+    PreferOne,
+    PreferZero,
+    Error,
+    /// Leave the column out: no filtering on it, and a zero bit in
+    /// gamma and epsilon.
+    Skip,
+}
+
+/// Tie policies of each rating. The default follows the submarine
+/// manual: oxygen keeps ones, CO2 keeps zeros and gamma has no ties.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct ReportPolicy {
+    pub power: TiePolicy,
+    pub oxygen: TiePolicy,
+    pub co2: TiePolicy,
+}
+
+impl Default for ReportPolicy {
+    fn default() -> ReportPolicy {
+        ReportPolicy {
+            power: TiePolicy::Error,
+            oxygen: TiePolicy::PreferOne,
+            co2: TiePolicy::PreferZero,
+        }
+    }
+}
+
+impl ColumnCount {
+    /// Most common bit, `None` on a tie.
+    pub fn most_common(&self) -> Option<bool> {
+        match self.ones.cmp(&self.zeros) {
+            Ordering::Greater => Some(true),
+            Ordering::Less => Some(false),
+            Ordering::Equal => None,
+        }
+    }
+
+    /// Least common bit, `None` on a tie.
+    pub fn least_common(&self) -> Option<bool> {
+        self.most_common().map(|bit| !bit)
+    }
+}
+
+impl TiePolicy {
+    /// Bit to keep for column `i`, `None` to skip the column.
+    fn resolve(&self, selected: Option<bool>, i: usize) -> io::Result<Option<bool>> {
+        match (selected, self) {
+            (Some(bit), _) => Ok(Some(bit)),
+            (None, TiePolicy::PreferOne) => Ok(Some(true)),
+            (None, TiePolicy::PreferZero) => Ok(Some(false)),
+            (None, TiePolicy::Skip) => Ok(None),
+            (None, TiePolicy::Error) => Err(invalid(format!("tie in column {}", i))),
+        }
+    }
+}
+
+/// Widest reading whose ratings still fit in a `u128`.
+pub const MAX_WIDTH: usize = 128;
+
 impl DiagnosticModule {
     pub fn from_file(input: &str) -> io::Result<DiagnosticModule> {
         let file = File::open(input)?;
-        let reader = BufReader::new(file);
-
-        Ok(DiagnosticModule {
-            reading: reader
-                .lines()
-                .map(|line| line.unwrap().into_bytes())
-                .collect(),
-        })
+        DiagnosticModule::from_reader(BufReader::new(file))
     }
 
-    pub fn generate_report(&self) -> DiagnosticReport {
-        let (gamma, epsilon) = self.power_consumption_report();
-        let oxygen = self.oxygen_report();
-        let co2 = self.co2_report();
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(input: &str) -> io::Result<DiagnosticModule> {
+        DiagnosticModule::from_reader(BufReader::new(input.as_bytes()))
+    }
 
-        DiagnosticReport {
-            power_consumption: gamma * epsilon,
-            life_support_rating: oxygen * co2,
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<DiagnosticModule> {
+        let mut module = DiagnosticModule {
+            reading: vec![],
+            width: 0,
+            columns: vec![],
+        };
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if module.reading.is_empty() {
+                if line.len() > MAX_WIDTH {
+                    return Err(invalid(format!(
+                        "reading width {} is not within 1..={}",
+                        line.len(),
+                        MAX_WIDTH
+                    )));
+                }
+                module.width = line.len();
+                module.columns = vec![vec![]; line.len()];
+            }
+            let value = parse_reading(line, module.width, module.reading.len() + 1)?;
+            module.push(value);
         }
+        if module.reading.is_empty() {
+            return Err(invalid("empty diagnostic report".to_string()));
+        }
+        Ok(module)
     }
 
-    fn power_consumption_report(&self) -> (i32, i32) {
-        let mut reading = vec![];
-        for line in &self.reading {
-            reading.push(line);
-        }
-        let mut gamma = 0;
-        let mut epsilon = 0;
-        for i in 0..self.reading[0].len() {
-            let most_common = self.most_common(&reading, i).unwrap();
-            if most_common == b'1' {
-                gamma |= 1;
-            } else {
-                epsilon |= 1;
+    fn push(&mut self, value: u128) {
+        let n = self.reading.len();
+        if n.is_multiple_of(64) {
+            for column in &mut self.columns {
+                column.push(0);
             }
-            gamma <<= 1;
-            epsilon <<= 1;
-        }
-        gamma >>= 1;
-        epsilon >>= 1;
-        (gamma, epsilon)
-    }
-
-    fn most_common(&self, reading: &[&Vec<u8>], i: usize) -> Option<u8> {
-        let mut ones = 0;
-        let mut zeros = 0;
-        for line in reading {
-            if line[i] == b'1' {
-                ones += 1;
-            } else {
-                zeros += 1;
+        }
+        for (i, column) in self.columns.iter_mut().enumerate() {
+            if bit(value, self.width, i) {
+                column[n / 64] |= 1 << (n % 64);
             }
         }
-        match ones.cmp(&zeros) {
-            Ordering::Greater => Some(b'1'),
-            Ordering::Less => Some(b'0'),
-            Ordering::Equal => None,
+        self.reading.push(value);
+    }
+
+    pub fn generate_report(&self) -> io::Result<DiagnosticReport> {
+        self.generate_report_with(&ReportPolicy::default())
+    }
+
+    pub fn generate_report_with(&self, policy: &ReportPolicy) -> io::Result<DiagnosticReport> {
+        let (gamma, epsilon) = self.power_consumption_report(policy.power)?;
+        let (oxygen, oxygen_trace) = self.filter_rating(ColumnCount::most_common, policy.oxygen)?;
+        let (co2, co2_trace) = self.filter_rating(ColumnCount::least_common, policy.co2)?;
+
+        Ok(DiagnosticReport {
+            power_consumption: BigUint::from(gamma) * epsilon,
+            life_support_rating: BigUint::from(oxygen) * co2,
+            gamma: self.rating(gamma),
+            epsilon: self.rating(epsilon),
+            oxygen_generator: self.rating(oxygen),
+            co2_scrubber: self.rating(co2),
+            column_counts: self.column_counts(),
+            oxygen_trace,
+            co2_trace,
+        })
+    }
+
+    fn rating(&self, value: u128) -> Rating {
+        Rating {
+            decimal: value,
+            binary: format!("{:0width$b}", value, width = self.width),
+        }
+    }
+
+    fn column_counts(&self) -> Vec<ColumnCount> {
+        let candidates = self.all_readings();
+        (0..self.width)
+            .map(|i| self.count_column(&candidates, i))
+            .collect()
+    }
+
+    fn power_consumption_report(&self, tie: TiePolicy) -> io::Result<(u128, u128)> {
+        rates(&self.column_counts(), tie)
+    }
+
+    fn all_readings(&self) -> Vec<u64> {
+        let n = self.reading.len();
+        let mut candidates = vec![u64::MAX; n.div_ceil(64)];
+        if !n.is_multiple_of(64) {
+            *candidates.last_mut().unwrap() = (1 << (n % 64)) - 1;
         }
+        candidates
     }
 
-    fn oxygen_report(&self) -> i32 {
-        let mut reading = vec![];
-        for line in &self.reading {
-            reading.push(line);
+    fn count_column(&self, candidates: &[u64], i: usize) -> ColumnCount {
+        let ones = count_ones(&self.columns[i], candidates);
+        ColumnCount {
+            ones,
+            zeros: count(candidates) - ones,
         }
-        for i in 0..self.reading[0].len() {
-            let most_common = self.most_common(&reading, i).unwrap_or(b'1');
-            let mut collector = vec![];
-            for line in &reading {
-                if line[i] == most_common {
-                    collector.push(*line);
-                }
-            }
-            reading = collector;
-            if reading.len() == 1 {
+    }
+
+    /// Narrow the readings down column by column, keeping those whose
+    /// bit is the one `criteria` selects from the column counts of the
+    /// remaining candidates, until only one is left. Also returns how
+    /// many candidates remained after each column.
+    pub fn filter_rating(
+        &self,
+        criteria: impl Fn(&ColumnCount) -> Option<bool>,
+        tie: TiePolicy,
+    ) -> io::Result<(u128, Vec<usize>)> {
+        let mut candidates = self.all_readings();
+        let mut trace = vec![];
+        for i in 0..self.width {
+            if count(&candidates) == 1 {
                 break;
             }
+            let count_i = self.count_column(&candidates, i);
+            if let Some(wanted) = tie.resolve(criteria(&count_i), i)? {
+                for (c, &col) in candidates.iter_mut().zip(&self.columns[i]) {
+                    *c &= if wanted { col } else { !col };
+                }
+            }
+            trace.push(count(&candidates));
         }
-        let mut oxygen = 0;
-        for &b in reading[0] {
-            oxygen |= if b == b'1' { 1 } else { 0 };
-            oxygen <<= 1;
+        let mut values = candidates.iter().enumerate().flat_map(|(w, &word)| {
+            (0..64)
+                .filter(move |b| word >> b & 1 == 1)
+                .map(move |b| self.reading[w * 64 + b])
+        });
+        let value = values
+            .next()
+            .ok_or_else(|| invalid("no reading matches the bit criteria".to_string()))?;
+        // Duplicates of one reading agree, distinct readings leave it open.
+        if values.any(|other| other != value) {
+            return Err(invalid(format!(
+                "{} readings are left after the last column",
+                count(&candidates)
+            )));
         }
-        oxygen >> 1
+        Ok((value, trace))
     }
+}
 
-    fn co2_report(&self) -> i32 {
-        let mut reading = vec![];
-        for line in &self.reading {
-            reading.push(line);
+/// Gamma and epsilon rates from the column counts.
+fn rates(counts: &[ColumnCount], tie: TiePolicy) -> io::Result<(u128, u128)> {
+    let mut gamma = 0;
+    let mut epsilon = 0;
+    for (i, count) in counts.iter().enumerate() {
+        gamma <<= 1;
+        epsilon <<= 1;
+        match tie.resolve(count.most_common(), i)? {
+            Some(true) => gamma |= 1,
+            Some(false) => epsilon |= 1,
+            None => (),
         }
-        for i in 0..self.reading[0].len() {
-            let most_common = self.most_common(&reading, i).unwrap_or(b'1');
-            let least_common = if most_common == b'1' { b'0' } else { b'1' };
-            let mut collector = vec![];
-            for line in &reading {
-                if line[i] == least_common {
-                    collector.push(*line);
-                }
-            }
-            reading = collector;
-            if reading.len() == 1 {
-                break;
-            }
+    }
+    Ok((gamma, epsilon))
+}
+
+impl fmt::Display for DiagnosticReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "power consumption {} = gamma {} ({}) * epsilon {} ({})",
+            self.power_consumption,
+            self.gamma.binary,
+            self.gamma.decimal,
+            self.epsilon.binary,
+            self.epsilon.decimal
+        )?;
+        writeln!(
+            f,
+            "life support rating {} = oxygen generator {} ({}) * CO2 scrubber {} ({})",
+            self.life_support_rating,
+            self.oxygen_generator.binary,
+            self.oxygen_generator.decimal,
+            self.co2_scrubber.binary,
+            self.co2_scrubber.decimal
+        )?;
+        writeln!(f, "column  ones  zeros")?;
+        for (i, count) in self.column_counts.iter().enumerate() {
+            writeln!(f, "{:>6} {:>5} {:>6}", i, count.ones, count.zeros)?;
         }
-        let mut co2 = 0;
-        for &b in reading[0] {
-            co2 |= if b == b'1' { 1 } else { 0 };
-            co2 <<= 1;
+        writeln!(
+            f,
+            "oxygen generator candidates: {}",
+            trace(&self.oxygen_trace)
+        )?;
+        write!(f, "CO2 scrubber candidates: {}", trace(&self.co2_trace))
+    }
+}
+
+fn trace(trace: &[usize]) -> String {
+    trace
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+fn parse_reading(line: &str, width: usize, n: usize) -> io::Result<u128> {
+    if line.len() != width {
+        return Err(invalid(format!(
+            "line {} has {} bits, expected {}",
+            n,
+            line.len(),
+            width
+        )));
+    }
+    let mut value = 0;
+    for b in line.bytes() {
+        value <<= 1;
+        match b {
+            b'0' => (),
+            b'1' => value |= 1,
+            _ => {
+                return Err(invalid(format!(
+                    "line {} has non-binary digit {:?}",
+                    n, b as char
+                )))
+            }
         }
-        co2 >> 1
     }
+    Ok(value)
+}
+
+/// Bit of column `i`, counting from the most significant digit.
+fn bit(value: u128, width: usize, i: usize) -> bool {
+    value >> (width - 1 - i) & 1 == 1
+}
+
+fn count(set: &[u64]) -> usize {
+    set.iter().map(|w| w.count_ones() as usize).sum()
+}
+
+fn count_ones(column: &[u64], candidates: &[u64]) -> usize {
+    column
+        .iter()
+        .zip(candidates)
+        .map(|(col, c)| (col & c).count_ones() as usize)
+        .sum()
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{DiagnosticModule, DiagnosticReport};
+    use num_bigint::BigUint;
+
+    use super::{ColumnCount, DiagnosticModule, DiagnosticReport, Rating, ReportPolicy, TiePolicy};
 
     #[test]
     fn test_day3() {
-        let mut diagnostic = DiagnosticModule { reading: vec![] };
-        {
-            let this = &mut diagnostic;
-            let reading = vec![
-                b"00100".to_vec(),
-                b"11110".to_vec(),
-                b"10110".to_vec(),
-                b"10111".to_vec(),
-                b"10101".to_vec(),
-                b"01111".to_vec(),
-                b"00111".to_vec(),
-                b"11100".to_vec(),
-                b"10000".to_vec(),
-                b"11001".to_vec(),
-                b"00010".to_vec(),
-                b"01010".to_vec(),
-            ];
-            this.reading = reading;
+        let diagnostic = DiagnosticModule::from_str(
+            "00100
+            11110
+            10110
+            10111
+            10101
+            01111
+            00111
+            11100
+            10000
+            11001
+            00010
+            01010",
+        )
+        .expect("parse error");
+        let rating = |decimal, binary: &str| Rating {
+            decimal,
+            binary: binary.to_string(),
         };
+        let count = |ones, zeros| ColumnCount { ones, zeros };
         assert_eq!(
             DiagnosticReport {
-                power_consumption: 198,
-                life_support_rating: 230,
+                power_consumption: 198u32.into(),
+                life_support_rating: 230u32.into(),
+                gamma: rating(22, "10110"),
+                epsilon: rating(9, "01001"),
+                oxygen_generator: rating(23, "10111"),
+                co2_scrubber: rating(10, "01010"),
+                column_counts: vec![
+                    count(7, 5),
+                    count(5, 7),
+                    count(8, 4),
+                    count(7, 5),
+                    count(5, 7)
+                ],
+                oxygen_trace: vec![7, 4, 3, 2, 1],
+                co2_trace: vec![5, 2, 1],
             },
-            diagnostic.generate_report()
+            diagnostic.generate_report().expect("report error")
+        );
+    }
+
+    #[test]
+    fn test_report_display() {
+        let diagnostic = DiagnosticModule::from_str("110\n100\n011\n").expect("parse error");
+        assert_eq!(
+            "power consumption 6 = gamma 110 (6) * epsilon 001 (1)
+life support rating 18 = oxygen generator 110 (6) * CO2 scrubber 011 (3)
+column  ones  zeros
+     0     2      1
+     1     2      1
+     2     1      2
+oxygen generator candidates: 2 -> 1
+CO2 scrubber candidates: 1",
+            diagnostic.generate_report().unwrap().to_string()
         );
     }
+
+    #[test]
+    fn test_wide_reading() {
+        let high = format!("1{}", "0".repeat(39));
+        let low = format!("0{}", "1".repeat(39));
+        let diagnostic = DiagnosticModule::from_str(&format!("{}\n{}\n{}\n", high, high, low))
+            .expect("parse error");
+        let report = diagnostic.generate_report().expect("report error");
+        let product = BigUint::from((1u128 << 39) * ((1 << 39) - 1));
+        assert_eq!(product, report.power_consumption);
+        assert_eq!(product, report.life_support_rating);
+
+        let widest = "1".repeat(128);
+        let diagnostic = DiagnosticModule::from_str(&widest).expect("parse error");
+        assert_eq!(
+            u128::MAX,
+            diagnostic
+                .filter_rating(ColumnCount::most_common, TiePolicy::PreferOne)
+                .unwrap()
+                .0
+        );
+        // Products of 128-bit ratings no longer fit in a u128.
+        let high = format!("1{}", "0".repeat(127));
+        let low = format!("0{}", "1".repeat(127));
+        let diagnostic = DiagnosticModule::from_str(&format!("{}\n{}\n{}\n", high, high, low))
+            .expect("parse error");
+        let report = diagnostic.generate_report().expect("report error");
+        let product = BigUint::from(1u128 << 127) * (u128::MAX >> 1);
+        assert_eq!(product, report.power_consumption);
+        assert_eq!(product, report.life_support_rating);
+    }
+
+    #[test]
+    fn test_invalid_reading() {
+        assert!(DiagnosticModule::from_str("").is_err());
+        assert!(DiagnosticModule::from_str("0101\n011\n").is_err());
+        assert!(DiagnosticModule::from_str("0102\n").is_err());
+        assert!(DiagnosticModule::from_str(&"1".repeat(129)).is_err());
+    }
+
+    #[test]
+    fn test_many_readings() {
+        // 201 readings spill over several bitset words.
+        let mut input = String::new();
+        for n in 0..201u32 {
+            input.push_str(&format!("{:08b}\n", (n * 37 + 11) % 256));
+        }
+        let diagnostic = DiagnosticModule::from_str(&input).expect("parse error");
+        assert_eq!(
+            (0b00011011, 0b11100100),
+            diagnostic
+                .power_consumption_report(TiePolicy::Error)
+                .unwrap()
+        );
+        let report = diagnostic.generate_report().expect("report error");
+        assert_eq!(0b00110111, report.oxygen_generator.decimal);
+        assert_eq!(0b10010001, report.co2_scrubber.decimal);
+    }
+
+    #[test]
+    fn test_tie_policy() {
+        let diagnostic = DiagnosticModule::from_str("10\n01\n").expect("parse error");
+        assert!(diagnostic.generate_report().is_err());
+
+        let policy = |power, oxygen, co2| ReportPolicy { power, oxygen, co2 };
+        let report = diagnostic
+            .generate_report_with(&policy(
+                TiePolicy::PreferOne,
+                TiePolicy::PreferOne,
+                TiePolicy::PreferZero,
+            ))
+            .unwrap();
+        assert_eq!((3, 0), (report.gamma.decimal, report.epsilon.decimal));
+        assert_eq!(
+            (2, 1),
+            (report.oxygen_generator.decimal, report.co2_scrubber.decimal)
+        );
+
+        let report = diagnostic
+            .generate_report_with(&policy(
+                TiePolicy::Skip,
+                TiePolicy::PreferOne,
+                TiePolicy::PreferOne,
+            ))
+            .unwrap();
+        assert_eq!((0, 0), (report.gamma.decimal, report.epsilon.decimal));
+        assert_eq!(2, report.oxygen_generator.decimal);
+        assert_eq!(2, report.co2_scrubber.decimal);
+        // Skipping every column leaves both readings standing.
+        assert!(diagnostic
+            .filter_rating(ColumnCount::most_common, TiePolicy::Skip)
+            .is_err());
+        let duplicates = DiagnosticModule::from_str("10\n10\n").expect("parse error");
+        assert_eq!(
+            (2, vec![2, 2]),
+            duplicates
+                .filter_rating(ColumnCount::most_common, TiePolicy::Skip)
+                .unwrap()
+        );
+
+        assert!(diagnostic
+            .generate_report_with(&policy(
+                TiePolicy::PreferZero,
+                TiePolicy::Error,
+                TiePolicy::PreferZero
+            ))
+            .is_err());
+    }
+
+    #[test]
+    fn test_custom_criteria() {
+        let diagnostic = DiagnosticModule::from_str("100\n110\n011\n001\n000\n").unwrap();
+        // Keep ones as long as at least a third of the candidates have them.
+        let (value, trace) = diagnostic
+            .filter_rating(|c| Some(c.ones * 3 >= c.ones + c.zeros), TiePolicy::Error)
+            .unwrap();
+        assert_eq!(0b110, value);
+        assert_eq!(vec![2, 1], trace);
+    }
 }
@@ -0,0 +1,161 @@
+use std::{collections::VecDeque, io};
+
+use num_bigint::BigUint;
+
+use super::{parse_reading, rates, ColumnCount, TiePolicy, MAX_WIDTH};
+use crate::invalid;
+
+/// Live view of the diagnostic report, fed one reading at a time. With a
+/// window only the last readings count, otherwise every reading does.
+pub struct DiagnosticMonitor {
+    width: usize,
+    window: Option<usize>,
+    recent: VecDeque<u128>,
+    ones: Vec<usize>,
+    seen: usize,
+    total: usize,
+}
+
+impl DiagnosticMonitor {
+    pub fn new(width: usize) -> io::Result<DiagnosticMonitor> {
+        if width == 0 || width > MAX_WIDTH {
+            return Err(invalid(format!(
+                "reading width {} is not within 1..={}",
+                width, MAX_WIDTH
+            )));
+        }
+        Ok(DiagnosticMonitor {
+            width,
+            window: None,
+            recent: VecDeque::new(),
+            ones: vec![0; width],
+            seen: 0,
+            total: 0,
+        })
+    }
+
+    pub fn with_window(width: usize, window: usize) -> io::Result<DiagnosticMonitor> {
+        if window == 0 {
+            return Err(invalid("empty monitor window".to_string()));
+        }
+        Ok(DiagnosticMonitor {
+            window: Some(window),
+            ..DiagnosticMonitor::new(width)?
+        })
+    }
+
+    pub fn push(&mut self, line: &str) -> io::Result<()> {
+        let value = parse_reading(line.trim(), self.width, self.seen + 1)?;
+        self.push_value(value)
+    }
+
+    /// Like `push`, for a reading already packed into its low `width` bits.
+    pub fn push_value(&mut self, value: u128) -> io::Result<()> {
+        if value.checked_shr(self.width as u32).unwrap_or(0) != 0 {
+            return Err(invalid(format!(
+                "reading {:#b} is wider than {} bits",
+                value, self.width
+            )));
+        }
+        self.seen += 1;
+        self.total += 1;
+        self.add(value, 1);
+        if let Some(window) = self.window {
+            self.recent.push_back(value);
+            if self.recent.len() > window {
+                let old = self.recent.pop_front().unwrap();
+                self.total -= 1;
+                self.add(old, -1);
+            }
+        }
+        Ok(())
+    }
+
+    fn add(&mut self, value: u128, sign: isize) {
+        for (i, ones) in self.ones.iter_mut().enumerate() {
+            if value >> (self.width - 1 - i) & 1 == 1 {
+                *ones = ones.wrapping_add_signed(sign);
+            }
+        }
+    }
+
+    /// Readings currently counted.
+    pub fn len(&self) -> usize {
+        self.total
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    pub fn column_counts(&self) -> Vec<ColumnCount> {
+        self.ones
+            .iter()
+            .map(|&ones| ColumnCount {
+                ones,
+                zeros: self.total - ones,
+            })
+            .collect()
+    }
+
+    /// Current gamma and epsilon rates.
+    pub fn rates(&self, tie: TiePolicy) -> io::Result<(u128, u128)> {
+        rates(&self.column_counts(), tie)
+    }
+
+    pub fn power_consumption(&self, tie: TiePolicy) -> io::Result<BigUint> {
+        let (gamma, epsilon) = self.rates(tie)?;
+        Ok(BigUint::from(gamma) * epsilon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::DiagnosticMonitor;
+    use crate::submarine::diagnostic::TiePolicy;
+
+    const REPORT: [&str; 12] = [
+        "00100", "11110", "10110", "10111", "10101", "01111", "00111", "11100", "10000", "11001",
+        "00010", "01010",
+    ];
+
+    #[test]
+    fn test_running_rates() {
+        let mut monitor = DiagnosticMonitor::new(5).unwrap();
+        assert!(monitor.rates(TiePolicy::Error).is_err());
+        for line in REPORT {
+            monitor.push(line).expect("bad reading");
+        }
+        assert_eq!(12, monitor.len());
+        assert_eq!((22, 9), monitor.rates(TiePolicy::Error).unwrap());
+        assert_eq!(
+            BigUint::from(198u32),
+            monitor.power_consumption(TiePolicy::Error).unwrap()
+        );
+        assert!(monitor.push("0101").is_err());
+        assert!(monitor.push("01021").is_err());
+        assert!(monitor.push_value(0b100000).is_err());
+        assert_eq!(12, monitor.len());
+        monitor.push_value(0b11111).unwrap();
+        assert_eq!(13, monitor.len());
+
+        let mut wide = DiagnosticMonitor::new(128).unwrap();
+        wide.push_value(u128::MAX).unwrap();
+        assert_eq!(1, wide.column_counts()[0].ones);
+    }
+
+    #[test]
+    fn test_window() {
+        let mut monitor = DiagnosticMonitor::with_window(5, 3).unwrap();
+        for line in REPORT {
+            monitor.push(line).expect("bad reading");
+        }
+        // Only 11001, 00010 and 01010 are left in the window.
+        assert_eq!(3, monitor.len());
+        assert_eq!((0b01010, 0b10101), monitor.rates(TiePolicy::Error).unwrap());
+        assert_eq!(1, monitor.column_counts()[0].ones);
+        assert!(DiagnosticMonitor::with_window(5, 0).is_err());
+    }
+}
@@ -1,11 +1,17 @@
 use std::{
-    collections::VecDeque,
     fs::File,
     io::{self, Read},
 };
 
+use num_bigint::BigUint;
+
+use super::population::{
+    history::{History, PopulationTable},
+    LifeCycle, Population,
+};
+
 pub struct LanternFishSim {
-    pool: VecDeque<usize>,
+    population: Population,
 }
 
 impl LanternFishSim {
@@ -17,22 +23,37 @@ impl LanternFishSim {
     }
 
     fn init_pool(input: &str) -> io::Result<LanternFishSim> {
-        let mut pool: VecDeque<_> = vec![0; 9].into();
-        input
+        let timers = input
             .trim()
             .split(',')
             .map(|s| s.parse::<usize>().unwrap())
-            .for_each(|num| pool[num] += 1);
-        Ok(LanternFishSim { pool })
+            .collect::<Vec<_>>();
+        Ok(LanternFishSim {
+            population: Population::new(LifeCycle::LANTERNFISH, &timers)?,
+        })
+    }
+
+    /// Head count after `days`, failing instead of overflowing.
+    pub fn run(&self, days: usize) -> io::Result<usize> {
+        self.population.run(days)
+    }
+
+    pub fn history(&self) -> History {
+        self.population.history()
+    }
+
+    pub fn table(&self, days: usize) -> PopulationTable {
+        self.population.table(days)
     }
 
-    pub fn run(&self, days: usize) -> usize {
-        let mut pool = self.pool.clone();
-        for _ in 0..days {
-            pool.rotate_left(1);
-            pool[6] += pool[8];
-        }
-        pool.iter().sum()
+    pub fn run_big(&self, days: usize) -> BigUint {
+        self.population.run_big(days)
+    }
+
+    /// Head count after `days` modulo `modulus`, in `O(log(days))` steps.
+    /// `None` for a zero modulus.
+    pub fn project_mod(&self, days: u64, modulus: u64) -> Option<u64> {
+        self.population.project_mod(days, modulus)
     }
 }
 
@@ -43,7 +64,30 @@ mod tests {
     #[test]
     fn test_day6() {
         let sim = LanternFishSim::init_pool("3,4,3,1,2").expect("init error");
-        assert_eq!(5934, sim.run(80));
-        assert_eq!(26_984_457_539, sim.run(256));
+        assert_eq!(5934, sim.run(80).unwrap());
+        assert_eq!(26_984_457_539, sim.run(256).unwrap());
+    }
+
+    #[test]
+    fn test_project_mod() {
+        let sim = LanternFishSim::init_pool("3,4,3,1,2").expect("init error");
+        assert_eq!(Some(5934 % 97), sim.project_mod(80, 97));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let sim = LanternFishSim::init_pool("3,4,3,1,2").expect("init error");
+        assert!(sim.run(2000).is_err());
+        assert_eq!(sim.run_big(80), 5934u32.into());
+    }
+
+    #[test]
+    fn test_history() {
+        let sim = LanternFishSim::init_pool("3,4,3,1,2").expect("init error");
+        assert_eq!(
+            Some(5),
+            sim.history().next().and_then(|census| census.total())
+        );
+        assert_eq!(Some(5934), sim.table(80).rows[80].total());
     }
 }
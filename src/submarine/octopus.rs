@@ -1,5 +1,4 @@
 use std::{
-    borrow::BorrowMut,
     cell::{Cell, RefCell},
     collections::VecDeque,
     fs::File,
@@ -123,19 +122,19 @@ impl OctopusSim {
         for _ in 0..turns {
             for row in &mut octopus {
                 for o in row {
-                    RefCell::borrow_mut(&o).step();
+                    RefCell::borrow_mut(o).step();
                 }
             }
             let mut to_charge = VecDeque::new();
             for row in &mut octopus {
                 for o in row {
-                    to_charge.extend(RefCell::borrow_mut(&o).react().into_iter());
+                    to_charge.extend(RefCell::borrow_mut(o).react());
                 }
             }
             while !to_charge.is_empty() {
                 for _ in 0..to_charge.len() {
                     let o = to_charge.pop_front().expect("empty queye");
-                    to_charge.extend(RefCell::borrow_mut(&o).charge().into_iter());
+                    to_charge.extend(RefCell::borrow_mut(&o).charge());
                 }
             }
         }
@@ -153,19 +152,19 @@ impl OctopusSim {
             let prev = self.flashes.get();
             for row in &mut octopus {
                 for o in row {
-                    RefCell::borrow_mut(&o).step();
+                    RefCell::borrow_mut(o).step();
                 }
             }
             let mut to_charge = VecDeque::new();
             for row in &mut octopus {
                 for o in row {
-                    to_charge.extend(RefCell::borrow_mut(&o).react().into_iter());
+                    to_charge.extend(RefCell::borrow_mut(o).react());
                 }
             }
             while !to_charge.is_empty() {
                 for _ in 0..to_charge.len() {
                     let o = to_charge.pop_front().expect("empty queye");
-                    to_charge.extend(RefCell::borrow_mut(&o).charge().into_iter());
+                    to_charge.extend(RefCell::borrow_mut(&o).charge());
                 }
             }
             if self.flashes.get() - prev == self.octopus.len() * self.octopus[0].len() {
@@ -0,0 +1,206 @@
+use super::{ControlModel, Position};
+
+pub struct MissionPlanner {
+    model: ControlModel,
+    depth_limit: Option<(i32, i32)>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Command {
+    Forward(i32),
+    Up(i32),
+    Down(i32),
+}
+
+impl MissionPlanner {
+    pub fn new(model: ControlModel) -> MissionPlanner {
+        MissionPlanner {
+            model,
+            depth_limit: None,
+        }
+    }
+
+    /// Keep the depth within `min..=max` after every command of the plan.
+    pub fn with_depth_limit(self, min: i32, max: i32) -> MissionPlanner {
+        MissionPlanner {
+            depth_limit: Some((min, max)),
+            ..self
+        }
+    }
+
+    /// Plan the shortest command sequence taking a fresh submarine to
+    /// `(x, depth)`, or `None` if the target cannot be reached.
+    pub fn plan(&self, x: i32, depth: i32) -> Option<Vec<String>> {
+        let commands = match self.model {
+            ControlModel::V1 => plan_v1(x, depth)?,
+            ControlModel::V2 => plan_v2(x, depth)?,
+        };
+        if !self.within_limit(&commands) {
+            return None;
+        }
+        Some(
+            commands
+                .iter()
+                .map(|command| match command {
+                    Command::Forward(unit) => format!("forward {}", unit),
+                    Command::Up(unit) => format!("up {}", unit),
+                    Command::Down(unit) => format!("down {}", unit),
+                })
+                .collect(),
+        )
+    }
+
+    fn within_limit(&self, commands: &[Command]) -> bool {
+        let (min, max) = match self.depth_limit {
+            Some(limit) => limit,
+            None => return true,
+        };
+        let control = self.model.control();
+        let mut pos = Position {
+            x: 0,
+            depth: 0,
+            aim: 0,
+        };
+        if !(min..=max).contains(&pos.depth) {
+            return false;
+        }
+        for &command in commands {
+            pos = match command {
+                Command::Forward(unit) => control.forward(&pos, unit),
+                Command::Up(unit) => control.up(&pos, unit),
+                Command::Down(unit) => control.down(&pos, unit),
+            };
+            if !(min..=max).contains(&pos.depth) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// The command changing depth or aim by a non-zero `unit`, or `None` if
+/// no single command can.
+fn vertical(unit: i32) -> Option<Command> {
+    match unit {
+        0 => None,
+        u if u > 0 => Some(Command::Down(u)),
+        u => u.checked_neg().map(Command::Up),
+    }
+}
+
+fn plan_v1(x: i32, depth: i32) -> Option<Vec<Command>> {
+    if x < 0 {
+        return None;
+    }
+    let mut commands = vec![];
+    if x > 0 {
+        commands.push(Command::Forward(x));
+    }
+    if depth != 0 {
+        commands.push(vertical(depth)?);
+    }
+    Some(commands)
+}
+
+fn plan_v2(x: i32, depth: i32) -> Option<Vec<Command>> {
+    if x < 0 || (x == 0 && depth != 0) {
+        return None;
+    }
+    if depth == 0 {
+        return plan_v1(x, 0);
+    }
+    // One aim change covers the whole distance.
+    if depth % x == 0 {
+        return Some(vec![vertical(depth / x)?, Command::Forward(x)]);
+    }
+    // Otherwise cruise flat first and dive along the longest tail that
+    // divides the depth. A tail of 1 always works, so three commands do.
+    let tail = divisors(depth.unsigned_abs())
+        .filter(|&d| d < x as u32)
+        .max()? as i32;
+    Some(vec![
+        Command::Forward(x - tail),
+        vertical(depth / tail)?,
+        Command::Forward(tail),
+    ])
+}
+
+fn divisors(n: u32) -> impl Iterator<Item = u32> {
+    (1..)
+        .take_while(move |&i| i * i <= n)
+        .filter(move |&i| n.is_multiple_of(i))
+        .flat_map(move |i| [i, n / i])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MissionPlanner;
+    use crate::submarine::{ControlModel, Submarine};
+
+    fn replay(model: ControlModel, plan: &[String]) -> (i32, i32) {
+        let mut submarine = Submarine::with_model(model);
+        submarine.run(plan);
+        submarine.position()
+    }
+
+    #[test]
+    fn test_plan_v1() {
+        let planner = MissionPlanner::new(ControlModel::V1);
+        let plan = planner.plan(15, 10).expect("no plan");
+        assert_eq!(vec!["forward 15", "down 10"], plan);
+        assert_eq!((15, 10), replay(ControlModel::V1, &plan));
+        assert_eq!(
+            (4, -3),
+            replay(ControlModel::V1, &planner.plan(4, -3).unwrap())
+        );
+        assert_eq!(None, planner.plan(-1, 0));
+        assert_eq!(None, planner.plan(0, i32::MIN));
+        assert_eq!(
+            (0, i32::MAX),
+            replay(ControlModel::V1, &planner.plan(0, i32::MAX).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_plan_v2() {
+        let planner = MissionPlanner::new(ControlModel::V2);
+        assert_eq!(vec!["down 6", "forward 10"], planner.plan(10, 60).unwrap());
+        assert_eq!(
+            vec!["forward 3", "down 1", "forward 7"],
+            planner.plan(10, 7).unwrap()
+        );
+        assert_eq!(
+            vec!["forward 14", "down 281", "forward 1"],
+            planner.plan(15, 281).unwrap()
+        );
+        for (x, depth) in [(15, 60), (900, 1), (15, 281), (7, -23), (1, -5), (5, 0)] {
+            let plan = planner.plan(x, depth).expect("no plan");
+            assert_eq!((x, depth), replay(ControlModel::V2, &plan));
+        }
+        assert_eq!(None, planner.plan(0, 5));
+    }
+
+    #[test]
+    fn test_replay_targets() {
+        for model in [ControlModel::V1, ControlModel::V2] {
+            let planner = MissionPlanner::new(model);
+            for x in 0..=30 {
+                for depth in -40..=40 {
+                    match planner.plan(x, depth) {
+                        Some(plan) => assert_eq!((x, depth), replay(model, &plan)),
+                        // Only V2 cannot dive without moving forward.
+                        None => assert!(model == ControlModel::V2 && x == 0 && depth != 0),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let planner = MissionPlanner::new(ControlModel::V2).with_depth_limit(0, 100);
+        assert!(planner.plan(10, 90).is_some());
+        assert_eq!(None, planner.plan(10, 101));
+        assert_eq!(None, planner.plan(10, -1));
+    }
+}
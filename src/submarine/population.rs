@@ -0,0 +1,351 @@
+pub mod history;
+
+use std::io;
+
+use num_bigint::BigUint;
+
+use crate::invalid;
+
+/// Life cycle of a species breeding on a fixed timer.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LifeCycle {
+    /// Days between two births of the same member.
+    pub timer_length: usize,
+    /// Extra days a newborn waits before its first cycle.
+    pub newborn_delay: usize,
+    pub offspring: usize,
+    /// Age in days at which a member dies, after breeding that day.
+    pub death_age: Option<usize>,
+}
+
+impl LifeCycle {
+    pub const LANTERNFISH: LifeCycle = LifeCycle {
+        timer_length: 7,
+        newborn_delay: 2,
+        offspring: 1,
+        death_age: None,
+    };
+
+    fn reset_timer(&self) -> usize {
+        self.timer_length - 1
+    }
+
+    fn newborn_timer(&self) -> usize {
+        self.reset_timer() + self.newborn_delay
+    }
+}
+
+/// Head counts of a population grouped by timer, and by age when members
+/// can die. The initial members start at age zero.
+#[derive(Clone)]
+pub struct Population {
+    cycle: LifeCycle,
+    counts: Vec<usize>,
+}
+
+impl Population {
+    pub fn new(cycle: LifeCycle, timers: &[usize]) -> io::Result<Population> {
+        if cycle.timer_length == 0 || cycle.death_age == Some(0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "timer length and death age must be positive",
+            ));
+        }
+        let mut population = Population {
+            cycle,
+            counts: vec![],
+        };
+        population.counts = vec![0; population.timers() * population.ages()];
+        for &timer in timers {
+            if timer >= population.timers() {
+                return Err(invalid(format!(
+                    "timer {} is above the newborn timer",
+                    timer
+                )));
+            }
+            population.counts[timer] += 1;
+        }
+        Ok(population)
+    }
+
+    fn timers(&self) -> usize {
+        self.cycle.newborn_timer() + 1
+    }
+
+    /// Age classes tracked, a single one for immortal species.
+    fn ages(&self) -> usize {
+        self.cycle.death_age.unwrap_or(1)
+    }
+
+    /// Where the members of a state are a day later, with how many of
+    /// them land there for each one.
+    fn transitions(&self, state: usize) -> Vec<(usize, usize)> {
+        let timers = self.timers();
+        let (age, timer) = (state / timers, state % timers);
+        let mut next = vec![];
+        if timer == 0 {
+            next.push((self.cycle.newborn_timer(), self.cycle.offspring));
+        }
+        let age = match self.cycle.death_age {
+            Some(death_age) if age + 1 >= death_age => return next,
+            Some(_) => age + 1,
+            None => 0,
+        };
+        let timer = match timer {
+            0 => self.cycle.reset_timer(),
+            t => t - 1,
+        };
+        next.push((age * timers + timer, 1));
+        next
+    }
+
+    /// Advance one day. Panics if a count overflows, see `run`.
+    pub fn step(&mut self) {
+        self.counts = self
+            .next_counts(&self.counts)
+            .expect("population overflows usize");
+    }
+
+    /// Counts a day after `counts`, or `None` if one overflows.
+    fn next_counts<T: Count>(&self, counts: &[T]) -> Option<Vec<T>> {
+        let mut next = (0..counts.len()).map(|_| T::zero()).collect::<Vec<T>>();
+        for (state, n) in counts.iter().enumerate() {
+            if n.is_zero() {
+                continue;
+            }
+            for (target, factor) in self.transitions(state) {
+                next[target].add_times(n, factor)?;
+            }
+        }
+        Some(next)
+    }
+
+    /// Head count, or `None` if it overflows.
+    pub fn total(&self) -> Option<usize> {
+        sum(&self.counts)
+    }
+
+    /// Head count after `days`, failing instead of overflowing.
+    pub fn run(&self, days: usize) -> io::Result<usize> {
+        let overflow = |day| invalid(format!("population overflows on day {}", day));
+        let mut counts = self.counts.clone();
+        for day in 1..=days {
+            counts = self.next_counts(&counts).ok_or_else(|| overflow(day))?;
+        }
+        sum(&counts).ok_or_else(|| overflow(days))
+    }
+
+    /// Exact head count after `days`, however large it grows.
+    pub fn run_big(&self, days: usize) -> BigUint {
+        let mut counts: Vec<BigUint> = self.counts.iter().map(|&n| BigUint::from(n)).collect();
+        for _ in 0..days {
+            counts = self.next_counts(&counts).unwrap();
+        }
+        counts.iter().sum()
+    }
+
+    /// Head count after `days` modulo `modulus`, squaring the one-day
+    /// transition matrix so that the cost grows with `log(days)`. `None`
+    /// for a zero modulus.
+    pub fn project_mod(&self, days: u64, modulus: u64) -> Option<u64> {
+        if modulus == 0 {
+            return None;
+        }
+        let mut power = self.transition_matrix(modulus);
+        let mut counts: Vec<u64> = self.counts.iter().map(|&n| n as u64 % modulus).collect();
+        let mut days = days;
+        while days > 0 {
+            if days & 1 == 1 {
+                counts = apply(&power, &counts, modulus);
+            }
+            power = multiply(&power, &power, modulus);
+            days >>= 1;
+        }
+        Some(counts.iter().fold(0, |sum, &n| {
+            ((sum as u128 + n as u128) % modulus as u128) as u64
+        }))
+    }
+
+    /// `matrix[to][from]` members land in state `to` for each one in `from`.
+    fn transition_matrix(&self, modulus: u64) -> Vec<Vec<u64>> {
+        let states = self.counts.len();
+        let mut matrix = vec![vec![0; states]; states];
+        for (from, transitions) in (0..states).map(|from| (from, self.transitions(from))) {
+            for (to, factor) in transitions {
+                matrix[to][from] = (matrix[to][from] + factor as u64) % modulus;
+            }
+        }
+        matrix
+    }
+}
+
+fn sum(counts: &[usize]) -> Option<usize> {
+    counts.iter().try_fold(0usize, |sum, &n| sum.checked_add(n))
+}
+
+/// A head count type: `usize` fails on overflow, `BigUint` never does.
+trait Count {
+    fn zero() -> Self;
+    fn is_zero(&self) -> bool;
+    /// Add `factor` times `n`, or `None` on overflow.
+    fn add_times(&mut self, n: &Self, factor: usize) -> Option<()>;
+}
+
+impl Count for usize {
+    fn zero() -> usize {
+        0
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0
+    }
+
+    fn add_times(&mut self, n: &usize, factor: usize) -> Option<()> {
+        *self = self.checked_add(n.checked_mul(factor)?)?;
+        Some(())
+    }
+}
+
+impl Count for BigUint {
+    fn zero() -> BigUint {
+        BigUint::default()
+    }
+
+    fn is_zero(&self) -> bool {
+        self.bits() == 0
+    }
+
+    fn add_times(&mut self, n: &BigUint, factor: usize) -> Option<()> {
+        *self += n * factor;
+        Some(())
+    }
+}
+
+fn multiply(a: &[Vec<u64>], b: &[Vec<u64>], modulus: u64) -> Vec<Vec<u64>> {
+    let n = a.len();
+    let mut product = vec![vec![0; n]; n];
+    for i in 0..n {
+        for k in 0..n {
+            if a[i][k] == 0 {
+                continue;
+            }
+            for j in 0..n {
+                product[i][j] = ((product[i][j] as u128 + a[i][k] as u128 * b[k][j] as u128)
+                    % modulus as u128) as u64;
+            }
+        }
+    }
+    product
+}
+
+fn apply(matrix: &[Vec<u64>], counts: &[u64], modulus: u64) -> Vec<u64> {
+    matrix
+        .iter()
+        .map(|row| {
+            row.iter().zip(counts).fold(0, |sum, (&m, &n)| {
+                ((sum as u128 + m as u128 * n as u128) % modulus as u128) as u64
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LifeCycle, Population};
+
+    #[test]
+    fn test_lanternfish_preset() {
+        let population = Population::new(LifeCycle::LANTERNFISH, &[3, 4, 3, 1, 2]).unwrap();
+        assert_eq!(26, population.run(18).unwrap());
+        assert_eq!(5934, population.run(80).unwrap());
+        assert!(Population::new(LifeCycle::LANTERNFISH, &[9]).is_err());
+    }
+
+    #[test]
+    fn test_project_mod() {
+        let population = Population::new(LifeCycle::LANTERNFISH, &[3, 4, 3, 1, 2]).unwrap();
+        for days in [0, 1, 18, 80, 256] {
+            assert_eq!(
+                Some(population.run(days).unwrap() as u64),
+                population.project_mod(days as u64, u64::MAX)
+            );
+        }
+        assert_eq!(Some(5934 % 97), population.project_mod(80, 97));
+        assert_eq!(Some(0), population.project_mod(80, 1));
+        assert_eq!(None, population.project_mod(80, 0));
+        assert!(
+            population
+                .project_mod(1_000_000_000_000, 1_000_000_007)
+                .unwrap()
+                < 1_000_000_007
+        );
+
+        let cycle = LifeCycle {
+            timer_length: 1,
+            newborn_delay: 0,
+            offspring: 1,
+            death_age: Some(3),
+        };
+        let population = Population::new(cycle, &[0]).unwrap();
+        assert_eq!(Some(24), population.project_mod(5, 1_000_000_007));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let population = Population::new(LifeCycle::LANTERNFISH, &[3, 4, 3, 1, 2]).unwrap();
+        assert_eq!(26_984_457_539, population.run(256).unwrap());
+        assert!(population.run(1000).is_err());
+        // Every bucket fits on day 495 but their sum does not.
+        assert!(population.run(495).is_err());
+        assert!(population.run_big(495) > usize::MAX.into());
+
+        let exact = population.run_big(1000);
+        assert_eq!(population.run_big(256), 26_984_457_539u64.into());
+        assert_eq!(
+            exact.clone() % 1_000_000_007u64,
+            population.project_mod(1000, 1_000_000_007).unwrap().into()
+        );
+        assert!(exact > usize::MAX.into());
+    }
+
+    #[test]
+    fn test_custom_cycle() {
+        // Breeds every other day with twins, newborns join right away.
+        let cycle = LifeCycle {
+            timer_length: 2,
+            newborn_delay: 0,
+            offspring: 2,
+            death_age: None,
+        };
+        let population = Population::new(cycle, &[0]).unwrap();
+        assert_eq!(
+            [1, 3, 3, 9, 9, 27],
+            [0, 1, 2, 3, 4, 5].map(|d| population.run(d).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_death_age() {
+        // Breeds daily and dies on its third day, breeding one last time.
+        let cycle = LifeCycle {
+            timer_length: 1,
+            newborn_delay: 0,
+            offspring: 1,
+            death_age: Some(3),
+        };
+        let population = Population::new(cycle, &[0]).unwrap();
+        // Births per day follow the tribonacci numbers.
+        assert_eq!(
+            [1, 2, 4, 7, 13, 24],
+            [0, 1, 2, 3, 4, 5].map(|d| population.run(d).unwrap())
+        );
+        assert!(Population::new(
+            LifeCycle {
+                death_age: Some(0),
+                ..cycle
+            },
+            &[0]
+        )
+        .is_err());
+    }
+}
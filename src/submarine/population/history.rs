@@ -0,0 +1,164 @@
+use std::{
+    fs::File,
+    io::{self, Write},
+};
+
+use super::Population;
+
+/// Head counts on one day, per timer value.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Census {
+    pub day: usize,
+    pub buckets: Vec<usize>,
+}
+
+impl Census {
+    /// Head count, or `None` if it overflows.
+    pub fn total(&self) -> Option<usize> {
+        super::sum(&self.buckets)
+    }
+}
+
+/// Day-by-day census of a population, starting with day 0. Panics once a
+/// bucket overflows, like `Population::step`.
+pub struct History {
+    population: Population,
+    day: usize,
+}
+
+impl Iterator for History {
+    type Item = Census;
+
+    fn next(&mut self) -> Option<Census> {
+        let timers = self.population.timers();
+        let mut buckets = vec![0usize; timers];
+        for (state, &n) in self.population.counts.iter().enumerate() {
+            buckets[state % timers] = buckets[state % timers]
+                .checked_add(n)
+                .expect("population overflows usize");
+        }
+        let census = Census {
+            day: self.day,
+            buckets,
+        };
+        self.population.step();
+        self.day += 1;
+        Some(census)
+    }
+}
+
+/// A collected history, from day 0 to the last day run.
+pub struct PopulationTable {
+    pub rows: Vec<Census>,
+}
+
+impl PopulationTable {
+    /// One line per day: the day, the count for each timer, then the total,
+    /// left empty if it overflows.
+    pub fn to_csv(&self) -> String {
+        let timers = self.rows.first().map_or(0, |row| row.buckets.len());
+        let mut csv = String::from("day");
+        for timer in 0..timers {
+            csv.push_str(&format!(",timer{}", timer));
+        }
+        csv.push_str(",total\n");
+        for row in &self.rows {
+            csv.push_str(&row.day.to_string());
+            for n in &row.buckets {
+                csv.push_str(&format!(",{}", n));
+            }
+            match row.total() {
+                Some(total) => csv.push_str(&format!(",{}\n", total)),
+                None => csv.push_str(",\n"),
+            }
+        }
+        csv
+    }
+
+    pub fn write_csv(&self, path: &str) -> io::Result<()> {
+        let mut file = File::create(path)?;
+        write!(file, "{}", self.to_csv())
+    }
+
+    /// Average daily growth over the table, compounded: `0.1` is 10% a day.
+    pub fn growth_rate(&self) -> Option<f64> {
+        let first = self.rows.first()?;
+        let last = self.rows.last()?;
+        let (start, end) = (first.total()?, last.total()?);
+        if start == 0 || last.day == first.day {
+            return None;
+        }
+        let ratio = end as f64 / start as f64;
+        Some(ratio.powf(1.0 / (last.day - first.day) as f64) - 1.0)
+    }
+
+    /// Days for the population to double at the average growth rate.
+    pub fn doubling_time(&self) -> Option<f64> {
+        match self.growth_rate()? {
+            rate if rate > 0.0 => Some(2f64.ln() / rate.ln_1p()),
+            _ => None,
+        }
+    }
+}
+
+impl Population {
+    pub fn history(&self) -> History {
+        History {
+            population: self.clone(),
+            day: 0,
+        }
+    }
+
+    pub fn table(&self, days: usize) -> PopulationTable {
+        PopulationTable {
+            rows: self.history().take(days + 1).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Census, PopulationTable};
+    use crate::submarine::population::{LifeCycle, Population};
+
+    #[test]
+    fn test_history() {
+        let population = Population::new(LifeCycle::LANTERNFISH, &[3, 4, 3, 1, 2]).unwrap();
+        let day2 = population.history().nth(2).unwrap();
+        assert_eq!(2, day2.day);
+        assert_eq!(vec![1, 2, 1, 0, 0, 0, 1, 0, 1], day2.buckets);
+
+        let table = population.table(18);
+        assert_eq!(19, table.rows.len());
+        assert_eq!(Some(26), table.rows[18].total());
+        let csv = table.to_csv();
+        assert!(csv.starts_with("day,timer0,timer1,timer2,timer3,timer4,timer5,timer6,timer7,timer8,total\n0,0,1,1,2,1,0,0,0,0,5\n"));
+        assert!(csv.ends_with("\n18,3,5,3,2,2,1,5,1,4,26\n"));
+    }
+
+    #[test]
+    fn test_growth() {
+        let population = Population::new(LifeCycle::LANTERNFISH, &[3, 4, 3, 1, 2]).unwrap();
+        let table = population.table(80);
+        let rate = table.growth_rate().unwrap();
+        assert!((rate - (5934f64 / 5.0).powf(1.0 / 80.0) + 1.0).abs() < 1e-12);
+        let doubling = table.doubling_time().unwrap();
+        assert!((2f64.powf(80.0 / doubling) - 5934.0 / 5.0).abs() < 1e-6);
+        assert_eq!(None, population.table(0).growth_rate());
+    }
+
+    #[test]
+    fn test_overflowing_total() {
+        let census = Census {
+            day: 0,
+            buckets: vec![usize::MAX, 1],
+        };
+        assert_eq!(None, census.total());
+        let table = PopulationTable { rows: vec![census] };
+        assert_eq!(
+            "day,timer0,timer1,total\n0,18446744073709551615,1,\n",
+            table.to_csv()
+        );
+        assert_eq!(None, table.growth_rate());
+    }
+}
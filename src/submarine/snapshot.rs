@@ -0,0 +1,160 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read, Write},
+};
+
+use super::ControlModel;
+use crate::invalid;
+
+/// Submarine state at a point of an instruction file, saved as
+/// `key value` lines.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Snapshot {
+    pub model: ControlModel,
+    pub x: i32,
+    pub depth: i32,
+    pub aim: i32,
+    pub index: usize,
+}
+
+impl Snapshot {
+    pub fn load(path: &str) -> io::Result<Snapshot> {
+        let mut file = File::open(path)?;
+        let mut buf = String::new();
+        file.read_to_string(&mut buf)?;
+        Snapshot::parse(&buf)
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let mut file = File::create(path)?;
+        write!(file, "{}", self)
+    }
+
+    pub fn parse(input: &str) -> io::Result<Snapshot> {
+        let mut model = None;
+        let mut x = None;
+        let mut depth = None;
+        let mut aim = None;
+        let mut index = None;
+        for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let (key, value) = line
+                .split_once(' ')
+                .ok_or_else(|| invalid(format!("malformed line {:?}", line)))?;
+            let value = value.trim();
+            match key {
+                "model" => {
+                    model = Some(match value {
+                        "v1" => ControlModel::V1,
+                        "v2" => ControlModel::V2,
+                        _ => return Err(invalid(format!("unknown control model {}", value))),
+                    })
+                }
+                "x" => x = Some(parse_value(key, value)?),
+                "depth" => depth = Some(parse_value(key, value)?),
+                "aim" => aim = Some(parse_value(key, value)?),
+                "index" => index = Some(parse_value(key, value)?),
+                _ => return Err(invalid(format!("unknown key {}", key))),
+            }
+        }
+        Ok(Snapshot {
+            model: model.ok_or_else(|| invalid("missing model".to_string()))?,
+            x: x.ok_or_else(|| invalid("missing x".to_string()))?,
+            depth: depth.ok_or_else(|| invalid("missing depth".to_string()))?,
+            aim: aim.ok_or_else(|| invalid("missing aim".to_string()))?,
+            index: index.ok_or_else(|| invalid("missing index".to_string()))?,
+        })
+    }
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let model = match self.model {
+            ControlModel::V1 => "v1",
+            ControlModel::V2 => "v2",
+        };
+        writeln!(f, "model {}", model)?;
+        writeln!(f, "x {}", self.x)?;
+        writeln!(f, "depth {}", self.depth)?;
+        writeln!(f, "aim {}", self.aim)?;
+        writeln!(f, "index {}", self.index)
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(key: &str, value: &str) -> io::Result<T> {
+    value
+        .parse()
+        .map_err(|_| invalid(format!("bad {} value {}", key, value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Snapshot;
+    use crate::submarine::{ControlModel, Submarine};
+
+    fn course() -> Vec<String> {
+        [
+            "forward 5",
+            "down 5",
+            "forward 8",
+            "up 3",
+            "down 8",
+            "forward 2",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+    }
+
+    #[test]
+    fn test_undo() {
+        let mut submarine = Submarine::v2();
+        submarine.run(&course());
+        assert_eq!(2, submarine.undo(2));
+        assert_eq!(4, submarine.command_index());
+        assert_eq!((13, 40), submarine.position());
+        assert_eq!(4, submarine.undo(10));
+        assert_eq!((0, 0), submarine.position());
+
+        let mut submarine = Submarine::v2().with_undo_limit(2);
+        submarine.run(&course());
+        assert_eq!(2, submarine.undo(3));
+        assert_eq!((13, 40), submarine.position());
+        let mut submarine = Submarine::v1().with_undo_limit(0);
+        submarine.run(&course());
+        assert_eq!(0, submarine.undo(1));
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let mut submarine = Submarine::v2();
+        submarine.run(&course()[..3]);
+        let snapshot = submarine.snapshot();
+        let text = snapshot.to_string();
+        assert_eq!("model v2\nx 13\ndepth 40\naim 5\nindex 3\n", text);
+        assert_eq!(snapshot, Snapshot::parse(&text).unwrap());
+        assert_eq!(ControlModel::V2, snapshot.model);
+        assert!(Snapshot::parse("model v3\n").is_err());
+        assert!(Snapshot::parse("model v1\nx 1\n").is_err());
+    }
+
+    #[test]
+    fn test_resume() {
+        let mut submarine = Submarine::v2();
+        submarine.run(&course()[..4]);
+        let path = std::env::temp_dir().join(format!(
+            "adventofcode2021_snapshot_test_{}.txt",
+            std::process::id()
+        ));
+        let path = path.to_str().unwrap();
+        submarine.snapshot().save(path).expect("save error");
+
+        let mut resumed = Submarine::restore(&Snapshot::load(path).expect("load error"));
+        std::fs::remove_file(path).ok();
+        resumed.resume(&course()).expect("resume error");
+        assert_eq!(6, resumed.command_index());
+        assert_eq!(900, resumed.report());
+        assert!(resumed.resume(&course()[..5]).is_err());
+        assert_eq!(0, Submarine::restore(&submarine.snapshot()).undo(1));
+    }
+}